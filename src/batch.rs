@@ -0,0 +1,127 @@
+use crate::metrics::RoundMetrics;
+use crate::parallel;
+use crate::simulator::{Protocol, Simulator};
+
+/// Outcome of one simulation run in a batch.
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    /// Seed the run was created with
+    pub seed: u64,
+
+    /// Alive node count after every round
+    pub alive_node_counts: Vec<usize>,
+
+    /// Metrics of every round
+    pub round_metrics: Vec<RoundMetrics>,
+}
+
+impl BatchResult {
+    /// Number of rounds simulated.
+    pub fn rounds(&self) -> usize {
+        self.alive_node_counts.len()
+    }
+
+    /// First round in which fewer nodes were alive than at the start (FND).
+    pub fn first_node_death_round(&self, node_count: usize) -> Option<usize> {
+        self.alive_node_counts
+            .iter()
+            .position(|&alive| alive < node_count)
+            .map(|index| index + 1)
+    }
+
+    /// First round in which no node was alive any more (LND).
+    pub fn last_node_death_round(&self) -> Option<usize> {
+        self.alive_node_counts
+            .iter()
+            .position(|&alive| alive == 0)
+            .map(|index| index + 1)
+    }
+}
+
+/// Runs one independent simulation per seed for at most `max_rounds` rounds
+/// (or until every node is dead).
+///
+/// `setup` builds the simulator and protocol of a run from its seed, typically
+/// with `Simulator::with_seed`. With the `parallel` feature the runs execute
+/// concurrently on the thread pool; results are returned in `seeds` order and are
+/// identical to a sequential batch.
+pub fn run_batch<P, F>(seeds: &[u64], max_rounds: usize, setup: F) -> Vec<BatchResult>
+where
+    P: Protocol,
+    F: Fn(u64) -> (Simulator, P) + Sync + Send,
+{
+    parallel::map(seeds, |&seed| {
+        let (mut simulator, mut protocol) = setup(seed);
+
+        let mut alive_node_counts = Vec::new();
+        let mut round_metrics = Vec::new();
+
+        while alive_node_counts.len() < max_rounds {
+            simulator.update(&mut protocol);
+
+            alive_node_counts.push(simulator.alive_node_count);
+            round_metrics.push(simulator.metrics.clone());
+
+            if simulator.alive_node_count == 0 {
+                break;
+            }
+        }
+
+        BatchResult {
+            seed,
+            alive_node_counts,
+            round_metrics,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leach::Leach;
+
+    fn setup(seed: u64) -> (Simulator, Leach) {
+        (Simulator::with_seed(200.0, 200.0, 40, seed), Leach::new(0.1))
+    }
+
+    #[test]
+    fn batch_runs_match_single_runs() {
+        let seeds = [3, 1, 4, 1, 5];
+        let results = run_batch(&seeds, 60, setup);
+
+        assert_eq!(results.iter().map(|result| result.seed).collect::<Vec<_>>(), seeds);
+        for result in &results {
+            let (mut simulator, mut leach) = setup(result.seed);
+            assert_eq!(result.rounds(), 60);
+
+            for (round, &alive) in result.alive_node_counts.iter().enumerate() {
+                simulator.update(&mut leach);
+                assert_eq!(alive, simulator.alive_node_count, "seed {}, round {round}", result.seed);
+                assert_eq!(
+                    format!("{:?}", result.round_metrics[round]),
+                    format!("{:?}", simulator.metrics),
+                    "seed {}, round {round}",
+                    result.seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn node_death_rounds() {
+        let result = BatchResult {
+            seed: 0,
+            alive_node_counts: vec![10, 10, 9, 4, 0],
+            round_metrics: Vec::new(),
+        };
+        assert_eq!(result.first_node_death_round(10), Some(3));
+        assert_eq!(result.last_node_death_round(), Some(5));
+
+        let result = BatchResult {
+            alive_node_counts: vec![10, 10],
+            ..result
+        };
+        assert_eq!(result.first_node_death_round(10), None);
+        assert_eq!(result.last_node_death_round(), None);
+    }
+}
@@ -0,0 +1,357 @@
+use crate::columns::NodeStore;
+use crate::config::{
+    BATTERY_CUTOFF_VOLTAGE_V,
+    BATTERY_EMPTY_VOLTAGE_V,
+    BATTERY_FULL_VOLTAGE_V,
+    BATTERY_PEUKERT_EXPONENT,
+    BATTERY_RATED_POWER_W,
+    KIBAM_AVAILABLE_FRACTION,
+    KIBAM_RATE_CONSTANT_PER_S,
+};
+use crate::metrics::RoundMetrics;
+
+/// How a battery turns the energy drawn by its node into lost charge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatteryModel {
+    /// Every Joule drawn removes one Joule of charge
+    Linear,
+
+    /// Rate-capacity effect: drawing `E` at average power `P` removes
+    /// `E · (P / rated_power_w)^(exponent − 1)` of charge
+    Peukert {
+        /// Peukert exponent k (1 → linear)
+        exponent: f32,
+
+        /// Discharge power at which the nominal capacity is delivered (W)
+        rated_power_w: f32,
+    },
+
+    /// Kinetic Battery Model (Manwell & McGowan): the load drains an available
+    /// well holding a fraction of the charge, which is refilled from a bound well
+    /// at a rate proportional to their height difference, so a resting battery
+    /// recovers. The battery is empty when the available well is.
+    KineticBattery {
+        /// Fraction c of the charge in the available well
+        available_fraction: f32,
+
+        /// Rate constant k of the flow between the wells (1/s)
+        rate_constant_per_s: f32,
+    },
+}
+
+impl BatteryModel {
+    /// Peukert model with the default parameters from `config`.
+    pub fn peukert() -> Self {
+        BatteryModel::Peukert {
+            exponent: BATTERY_PEUKERT_EXPONENT,
+            rated_power_w: BATTERY_RATED_POWER_W,
+        }
+    }
+
+    /// Kinetic Battery Model with the default parameters from `config`.
+    pub fn kinetic_battery() -> Self {
+        BatteryModel::KineticBattery {
+            available_fraction: KIBAM_AVAILABLE_FRACTION,
+            rate_constant_per_s: KIBAM_RATE_CONSTANT_PER_S,
+        }
+    }
+}
+
+/// Battery model shared by all nodes, with a linear open-circuit voltage curve.
+///
+/// - The voltage falls linearly from `full_voltage_v` to `empty_voltage_v` with the
+///   state of charge of the (available) charge
+/// - A node browns out once its voltage drops below `cutoff_voltage_v`, even if
+///   charge is left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryConfig {
+    /// How drawn energy removes charge
+    pub model: BatteryModel,
+
+    /// Voltage of a full battery (V)
+    pub full_voltage_v: f32,
+
+    /// Voltage of an empty battery (V)
+    pub empty_voltage_v: f32,
+
+    /// Voltage below which the node stops working (V)
+    pub cutoff_voltage_v: f32,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self::new(BatteryModel::Linear)
+    }
+}
+
+impl BatteryConfig {
+    /// Creates a battery configuration without early voltage cutoff.
+    pub fn new(model: BatteryModel) -> Self {
+        Self {
+            model,
+            full_voltage_v: BATTERY_FULL_VOLTAGE_V,
+            empty_voltage_v: BATTERY_EMPTY_VOLTAGE_V,
+            cutoff_voltage_v: BATTERY_CUTOFF_VOLTAGE_V,
+        }
+    }
+
+    /// Browns nodes out below `cutoff_voltage_v`.
+    pub fn with_cutoff_voltage(mut self, cutoff_voltage_v: f32) -> Self {
+        self.cutoff_voltage_v = cutoff_voltage_v;
+        self
+    }
+}
+
+/// Charge state of one node's battery.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Battery {
+    /// Nominal capacity (Joules)
+    capacity_j: f32,
+
+    /// Charge the load can draw from (Joules)
+    available_j: f32,
+
+    /// Charge in the KiBaM bound well (Joules, 0 for the other models)
+    bound_j: f32,
+}
+
+impl Battery {
+    /// A full battery of `capacity_j` Joules.
+    pub fn new(capacity_j: f32, model: &BatteryModel) -> Self {
+        let available_fraction = available_fraction(model);
+        Self {
+            capacity_j,
+            available_j: capacity_j * available_fraction,
+            bound_j: capacity_j * (1.0 - available_fraction),
+        }
+    }
+
+    /// Charge left in the battery (Joules).
+    pub fn remaining_j(&self) -> f32 {
+        self.available_j + self.bound_j
+    }
+
+    /// Open-circuit voltage (V).
+    pub fn voltage_v(&self, config: &BatteryConfig) -> f32 {
+        let available_capacity_j = self.capacity_j * available_fraction(&config.model);
+        let state_of_charge = if available_capacity_j > 0.0 {
+            (self.available_j / available_capacity_j).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        config.empty_voltage_v + (config.full_voltage_v - config.empty_voltage_v) * state_of_charge
+    }
+
+    /// Whether the node can no longer run on this battery.
+    pub fn is_depleted(&self, config: &BatteryConfig) -> bool {
+        self.available_j <= 0.0 || self.voltage_v(config) < config.cutoff_voltage_v
+    }
+
+    /// Stores up to `energy_j` Joules in the available well without exceeding the
+    /// nominal capacity; returns the energy actually stored.
+    ///
+    /// An overdrawn battery is emptied first: it cannot hold negative charge.
+    pub fn charge(&mut self, energy_j: f32) -> f32 {
+        self.available_j = self.available_j.max(0.0);
+        let stored_j = energy_j.min(self.capacity_j - self.remaining_j()).max(0.0);
+        self.available_j += stored_j;
+        stored_j
+    }
+
+    /// Draws `energy_j` Joules evenly over `duration_s` seconds.
+    pub fn draw(&mut self, model: &BatteryModel, energy_j: f32, duration_s: f32) {
+        match *model {
+            BatteryModel::Linear => self.available_j -= energy_j,
+            BatteryModel::Peukert { exponent, rated_power_w } => {
+                let power_w = if duration_s > 0.0 { energy_j / duration_s } else { 0.0 };
+                let rate_factor = if power_w > 0.0 && rated_power_w > 0.0 {
+                    (power_w / rated_power_w).powf(exponent - 1.0)
+                } else {
+                    1.0
+                };
+                self.available_j -= energy_j * rate_factor;
+            }
+            BatteryModel::KineticBattery { available_fraction, rate_constant_per_s } => {
+                if available_fraction <= 0.0
+                    || available_fraction >= 1.0
+                    || rate_constant_per_s <= 0.0
+                    || duration_s <= 0.0
+                {
+                    self.available_j -= energy_j;
+                    return;
+                }
+
+                // Closed-form KiBaM step under constant load, in f64: the recovery
+                // terms are differences of nearly equal numbers
+                let c = available_fraction as f64;
+                let k = rate_constant_per_s as f64 / (c * (1.0 - c));
+                let t = duration_s as f64;
+                let power_w = energy_j as f64 / t;
+                let (available_j, bound_j) = (self.available_j as f64, self.bound_j as f64);
+                let total_j = available_j + bound_j;
+                let decay = (-k * t).exp();
+                let ramp = k * t - 1.0 + decay;
+
+                self.available_j = (available_j * decay + (total_j * k * c - power_w) * (1.0 - decay) / k
+                    - power_w * c * ramp / k) as f32;
+                self.bound_j = (bound_j * decay + total_j * (1.0 - c) * (1.0 - decay)
+                    - power_w * (1.0 - c) * ramp / k) as f32;
+            }
+        }
+    }
+}
+
+/// The batteries of all nodes, indexed by node id.
+#[derive(Debug, Clone)]
+pub struct BatteryBank {
+    /// Model and voltage curve shared by all batteries
+    config: BatteryConfig,
+
+    /// One battery per node id
+    cells: Vec<Battery>,
+}
+
+impl BatteryBank {
+    /// Gives every node a full battery of its current remaining energy.
+    pub fn new<S: NodeStore + ?Sized>(config: BatteryConfig, nodes: &S) -> Self {
+        let cells = (0..nodes.node_count())
+            .map(|id| Battery::new(nodes.remaining_energy_j(id), &config.model))
+            .collect();
+        Self { config, cells }
+    }
+
+    /// Model and voltage curve shared by all batteries.
+    pub fn config(&self) -> &BatteryConfig {
+        &self.config
+    }
+
+    /// Battery of node `id` (`None` for nodes added after the last round).
+    pub fn cell(&self, id: usize) -> Option<&Battery> {
+        self.cells.get(id)
+    }
+
+    /// Charge left in node `id`'s battery, ignoring the voltage cutoff (Joules).
+    pub(crate) fn stored_j(&self, id: usize) -> Option<f32> {
+        self.cells.get(id).map(|cell| cell.remaining_j().max(0.0))
+    }
+
+    /// Charges node `id`'s battery with up to `energy_j` Joules and mirrors it into
+    /// `remaining_energy_j` (0 while the battery is still below the cutoff).
+    ///
+    /// Returns the energy actually stored.
+    pub(crate) fn charge<S: NodeStore + ?Sized>(&mut self, nodes: &mut S, id: usize, energy_j: f32) -> f32 {
+        let Some(cell) = self.cells.get_mut(id) else {
+            return 0.0;
+        };
+        let stored_j = cell.charge(energy_j);
+        nodes.set_remaining_energy_j(id, if cell.is_depleted(&self.config) { 0.0 } else { cell.remaining_j() });
+        stored_j
+    }
+
+    /// Draws the energy each alive node used this round from its battery.
+    ///
+    /// - `energy_before_j` holds every node's remaining energy at the start of the round
+    /// - Afterwards `remaining_energy_j` mirrors the battery; a depleted battery sets it
+    ///   to 0 so the protocols retire the node
+    /// - Nodes added since the last round get a full battery of their energy
+    pub(crate) fn apply_round<S: NodeStore + ?Sized>(
+        &mut self,
+        nodes: &mut S,
+        energy_before_j: &[f32],
+        duration_s: f32,
+        metrics: &mut RoundMetrics,
+    ) {
+        for (id, &before_j) in energy_before_j.iter().enumerate() {
+            if self.cells.len() <= id {
+                self.cells.push(Battery::new(before_j, &self.config.model));
+            }
+            if !nodes.is_alive(id) {
+                continue;
+            }
+
+            let remaining_j = nodes.remaining_energy_j(id);
+            let cell = &mut self.cells[id];
+            cell.draw(&self.config.model, (before_j - remaining_j).max(0.0), duration_s);
+
+            if cell.is_depleted(&self.config) {
+                if remaining_j > 0.0 {
+                    metrics.battery_cutoffs += 1;
+                }
+                nodes.set_remaining_energy_j(id, 0.0);
+            } else {
+                nodes.set_remaining_energy_j(id, cell.remaining_j());
+            }
+        }
+    }
+}
+
+/// Share of the charge the load can draw from directly.
+fn available_fraction(model: &BatteryModel) -> f32 {
+    match *model {
+        BatteryModel::KineticBattery { available_fraction, .. } => available_fraction.clamp(0.0, 1.0),
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() <= 1e-5 * expected.abs().max(1.0), "{actual} vs {expected}");
+    }
+
+    #[test]
+    fn peukert_loses_capacity_above_rated_power() {
+        let model = BatteryModel::Peukert { exponent: 1.2, rated_power_w: 0.1 };
+        let charge_lost = |energy_j: f32, duration_s: f32| {
+            let mut battery = Battery::new(100.0, &model);
+            battery.draw(&model, energy_j, duration_s);
+            100.0 - battery.remaining_j()
+        };
+
+        // 1 J at 0.1 W (rated), 1 W and 0.01 W
+        assert_close(charge_lost(1.0, 10.0), 1.0);
+        assert_close(charge_lost(1.0, 1.0), 10f32.powf(0.2));
+        assert_close(charge_lost(1.0, 100.0), 0.1f32.powf(0.2));
+    }
+
+    #[test]
+    fn kibam_conserves_charge_under_load() {
+        let model = BatteryModel::kinetic_battery();
+        let mut battery = Battery::new(100.0, &model);
+
+        battery.draw(&model, 5.0, 20.0);
+        assert_close(battery.remaining_j(), 95.0);
+    }
+
+    #[test]
+    fn kibam_recovers_at_rest() {
+        let model = BatteryModel::KineticBattery { available_fraction: 0.5, rate_constant_per_s: 0.01 };
+        let config = BatteryConfig::new(model);
+        let mut battery = Battery::new(100.0, &model);
+
+        // A burst drains the available well faster than the bound well refills it
+        battery.draw(&model, 30.0, 1.0);
+        let available_after_load_j = battery.available_j;
+        let voltage_after_load_v = battery.voltage_v(&config);
+
+        battery.draw(&model, 0.0, 1000.0);
+        assert_close(battery.remaining_j(), 70.0);
+        assert!(battery.available_j > available_after_load_j);
+        assert!(battery.voltage_v(&config) > voltage_after_load_v);
+
+        // At equilibrium both wells are equally full
+        assert_close(battery.available_j, 35.0);
+    }
+
+    #[test]
+    fn charge_respects_capacity() {
+        let model = BatteryModel::Linear;
+        let mut battery = Battery::new(10.0, &model);
+
+        battery.draw(&model, 4.0, 1.0);
+        assert_close(battery.charge(6.0), 4.0);
+        assert_close(battery.remaining_j(), 10.0);
+    }
+}
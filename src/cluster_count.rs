@@ -0,0 +1,252 @@
+use crate::clustering::{silhouette_score, sum_of_squared_errors, CentroidInit, Clusterer, KMeans};
+use crate::columns::NodeStore;
+use crate::config::{
+    ENERGY_FREE_SPACE_AMP_J,
+    ENERGY_MULTIPATH_AMP_J,
+    GAP_STATISTIC_REFERENCE_SETS,
+};
+use crate::node::Node;
+use glam::Vec2;
+use rand::{Rng, RngCore};
+use std::f32::consts::PI;
+
+/// How many clusters (and therefore cluster heads) to form.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClusterCount {
+    /// `ceil(p · alive nodes)` for a fixed CH probability `p`
+    Probability(f32),
+
+    /// Analytic optimum of the first-order radio model (see `optimal_cluster_count`)
+    Analytic,
+
+    /// Elbow of the K-Means SSE curve over `1..=max_clusters`
+    Elbow { max_clusters: usize },
+
+    /// K with the highest mean silhouette over `2..=max_clusters`
+    Silhouette { max_clusters: usize },
+
+    /// Tibshirani's gap statistic over `1..=max_clusters`
+    GapStatistic { max_clusters: usize },
+}
+
+impl ClusterCount {
+    /// Resolves the cluster count for the alive nodes of `nodes` in a deployment
+    /// area of `area_m2` square meters. Always at least 1 while any node is alive.
+    ///
+    /// The empirical strategies fit K-Means (K-Means++ seeding) once per candidate k.
+    pub fn resolve(&self, nodes: &dyn NodeStore, area_m2: f32, rng: &mut dyn RngCore) -> usize {
+        let alive: Vec<usize> = (0..nodes.node_count())
+            .filter(|&node_id| nodes.is_alive(node_id))
+            .collect();
+
+        if alive.is_empty() {
+            return 0;
+        }
+
+        let k = match *self {
+            ClusterCount::Probability(p) => (p * alive.len() as f32).ceil() as usize,
+            ClusterCount::Analytic => {
+                let mean_distance_to_sink = alive
+                    .iter()
+                    .map(|&node_id| nodes.distance_to_base_station_m(node_id))
+                    .sum::<f32>()
+                    / alive.len() as f32;
+                optimal_cluster_count(alive.len(), area_m2, mean_distance_to_sink)
+            }
+            ClusterCount::Elbow { max_clusters } => {
+                elbow_cluster_count(nodes, &alive, max_clusters, rng)
+            }
+            ClusterCount::Silhouette { max_clusters } => {
+                silhouette_cluster_count(nodes, &alive, max_clusters, rng)
+            }
+            ClusterCount::GapStatistic { max_clusters } => {
+                gap_statistic_cluster_count(nodes, &alive, max_clusters, rng)
+            }
+        };
+
+        k.clamp(1, alive.len())
+    }
+}
+
+/// Analytic optimal number of clusters for the first-order radio model
+/// (Heinzelman et al.):
+///
+/// `k_opt = sqrt(N / 2π) · sqrt(ε_fs / ε_mp) · M / d²_toBS`
+///
+/// where `M = sqrt(area)` is the side of an equivalent square field and
+/// `d_toBS` the (mean) distance from the nodes to the sink.
+pub fn optimal_cluster_count(n_nodes: usize, area_m2: f32, distance_to_sink_m: f32) -> usize {
+    if n_nodes == 0 {
+        return 0;
+    }
+
+    let field_side_m = area_m2.sqrt();
+    let amplifier_ratio = (ENERGY_FREE_SPACE_AMP_J / ENERGY_MULTIPATH_AMP_J).sqrt();
+    let distance_sq = distance_to_sink_m.max(1.0).powi(2);
+
+    let k_opt = (n_nodes as f32 / (2.0 * PI)).sqrt() * amplifier_ratio * field_side_m / distance_sq;
+
+    (k_opt.round() as usize).clamp(1, n_nodes)
+}
+
+/// Fits K-Means++ with `k` clusters on `indices` and returns the fitted model.
+fn fit_kmeans(nodes: &dyn NodeStore, indices: &[usize], k: usize, rng: &mut dyn RngCore) -> KMeans {
+    let mut kmeans = KMeans::new(k).with_init(CentroidInit::KMeansPlusPlus);
+    kmeans.fit_indices(nodes, indices, rng);
+    kmeans
+}
+
+/// Picks the "elbow" of the SSE curve: the k whose (normalized) point lies
+/// farthest below the straight line joining the first and last points.
+pub fn elbow_cluster_count(
+    nodes: &dyn NodeStore,
+    indices: &[usize],
+    max_clusters: usize,
+    rng: &mut dyn RngCore,
+) -> usize {
+    let max_k = max_clusters.min(indices.len());
+    if max_k <= 2 {
+        return max_k.max(1);
+    }
+
+    let sse: Vec<f32> = (1..=max_k)
+        .map(|k| {
+            let kmeans = fit_kmeans(nodes, indices, k, rng);
+            sum_of_squared_errors(nodes, kmeans.clusters(), kmeans.centers())
+        })
+        .collect();
+
+    let (first, last) = (sse[0], sse[max_k - 1]);
+    let range = (first - last).max(f32::EPSILON);
+
+    (1..=max_k)
+        .max_by(|&a, &b| {
+            // Distance below the chord in normalized (k, sse) space
+            let below = |k: usize| {
+                let t = (k - 1) as f32 / (max_k - 1) as f32;
+                let chord = 1.0 - t;
+                chord - (sse[k - 1] - last) / range
+            };
+            below(a).total_cmp(&below(b))
+        })
+        .unwrap_or(1)
+}
+
+/// Picks the k in `2..=max_clusters` with the highest mean silhouette coefficient.
+pub fn silhouette_cluster_count(
+    nodes: &dyn NodeStore,
+    indices: &[usize],
+    max_clusters: usize,
+    rng: &mut dyn RngCore,
+) -> usize {
+    let max_k = max_clusters.min(indices.len());
+    if max_k < 2 {
+        return 1;
+    }
+
+    (2..=max_k)
+        .map(|k| {
+            let kmeans = fit_kmeans(nodes, indices, k, rng);
+            (k, silhouette_score(nodes, kmeans.clusters()))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(k, _)| k)
+        .unwrap_or(1)
+}
+
+/// Tibshirani's gap statistic: the smallest k with `gap(k) ≥ gap(k+1) - s(k+1)`,
+/// where `gap(k) = E*[log W_k] - log W_k` is estimated from
+/// `GAP_STATISTIC_REFERENCE_SETS` uniform samples over the nodes' bounding box.
+pub fn gap_statistic_cluster_count(
+    nodes: &dyn NodeStore,
+    indices: &[usize],
+    max_clusters: usize,
+    rng: &mut dyn RngCore,
+) -> usize {
+    let max_k = max_clusters.min(indices.len());
+    if max_k < 2 {
+        return 1;
+    }
+
+    let mut min = Vec2::splat(f32::INFINITY);
+    let mut max = Vec2::splat(f32::NEG_INFINITY);
+    for &node_id in indices {
+        min = min.min(nodes.position(node_id));
+        max = max.max(nodes.position(node_id));
+    }
+
+    // Uniform reference sets over the bounding box (same size as the data)
+    let reference_sets: Vec<Vec<Node>> = (0..GAP_STATISTIC_REFERENCE_SETS)
+        .map(|_| {
+            (0..indices.len())
+                .map(|id| {
+                    let x = min.x + rng.random::<f32>() * (max.x - min.x);
+                    let y = min.y + rng.random::<f32>() * (max.y - min.y);
+                    Node::new(id, Vec2::new(x, y))
+                })
+                .collect()
+        })
+        .collect();
+    let reference_indices: Vec<usize> = (0..indices.len()).collect();
+
+    let mut log_dispersion = |wsn: &dyn NodeStore, subset: &[usize], k: usize| -> f32 {
+        let kmeans = fit_kmeans(wsn, subset, k, rng);
+        sum_of_squared_errors(wsn, kmeans.clusters(), kmeans.centers())
+            .max(f32::EPSILON)
+            .ln()
+    };
+
+    let mut gaps = Vec::with_capacity(max_k);
+    let mut spreads = Vec::with_capacity(max_k);
+
+    for k in 1..=max_k {
+        let observed = log_dispersion(nodes, indices, k);
+        let reference: Vec<f32> = reference_sets
+            .iter()
+            .map(|set| log_dispersion(set, &reference_indices, k))
+            .collect();
+
+        let b = reference.len() as f32;
+        let mean = reference.iter().sum::<f32>() / b;
+        let std_dev = (reference.iter().map(|r| (r - mean).powi(2)).sum::<f32>() / b).sqrt();
+
+        gaps.push(mean - observed);
+        spreads.push(std_dev * (1.0 + 1.0 / b).sqrt());
+    }
+
+    (1..max_k)
+        .find(|&k| gaps[k - 1] >= gaps[k] - spreads[k])
+        .unwrap_or(max_k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analytic_count_matches_heinzelman_example() {
+        // 100 nodes on a 100 m × 100 m field with the sink 75–185 m away:
+        // k_opt ≈ 6.2 at 75 m and ≈ 1.0 at 185 m
+        assert_eq!(optimal_cluster_count(100, 100.0 * 100.0, 75.0), 6);
+        assert_eq!(optimal_cluster_count(100, 100.0 * 100.0, 185.0), 1);
+
+        let counts: Vec<usize> = (75..=185)
+            .step_by(10)
+            .map(|distance| optimal_cluster_count(100, 100.0 * 100.0, distance as f32))
+            .collect();
+        assert!(counts.windows(2).all(|pair| pair[1] <= pair[0]));
+    }
+
+    #[test]
+    fn analytic_count_scales_with_sqrt_of_node_count() {
+        // Quadrupling N doubles k_opt
+        assert_eq!(optimal_cluster_count(400, 100.0 * 100.0, 75.0), 12);
+    }
+
+    #[test]
+    fn analytic_count_is_clamped() {
+        assert_eq!(optimal_cluster_count(0, 100.0 * 100.0, 75.0), 0);
+        assert_eq!(optimal_cluster_count(10, 100.0 * 100.0, 1000.0), 1);
+        assert_eq!(optimal_cluster_count(10, 100.0 * 100.0, 0.0), 10);
+    }
+}
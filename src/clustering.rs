@@ -1,27 +1,119 @@
-use crate::node::Node;
+use crate::columns::NodeStore;
+use crate::parallel;
+use crate::spatial::SpatialGrid;
 use glam::Vec2;
+use rand::{Rng, RngCore};
 use rand::seq::index::sample;
 
+mod agglomerative;
+mod balanced;
+mod dbscan;
+mod kmedoids;
+
+pub use agglomerative::{Agglomerative, Linkage};
+pub use balanced::BalancedKMeans;
+pub use dbscan::Dbscan;
+pub use kmedoids::KMedoids;
+
 /// Maximum number of iterations allowed for the K-Means algorithm convergence.
 const MAX_ITER: usize = 100;
 
 /// Convergence threshold: maximum allowed centroid movement (in meters) to stop early.
 const EPS: f32 = 1e-4;
 
+/// Common interface of the clustering algorithms that can partition the network.
+///
+/// Implementations are fitted on a subset of node ids and always report
+/// assignments by original node id.
+pub trait Clusterer {
+    /// Sets the number of clusters for the next fit (ignored by algorithms that
+    /// determine the cluster count themselves, e.g. DBSCAN).
+    fn set_n_clusters(&mut self, n_clusters: usize);
+
+    /// Fits the model to the nodes listed in `indices` (ids into `wsn`).
+    fn fit_indices(&mut self, wsn: &dyn NodeStore, indices: &[usize], rng: &mut dyn RngCore);
+
+    /// Cluster centers after the last fit (centroids or medoid positions).
+    fn centers(&self) -> &[Vec2];
+
+    /// Cluster of every node id after the last fit (`None` → not clustered).
+    fn clusters(&self) -> &[Option<usize>];
+
+    /// Fits the model to the alive nodes only; dead nodes get no cluster.
+    fn fit_alive(&mut self, wsn: &dyn NodeStore, rng: &mut dyn RngCore) {
+        let indices: Vec<usize> = (0..wsn.node_count())
+            .filter(|&node_id| wsn.is_alive(node_id))
+            .collect();
+        self.fit_indices(wsn, &indices, rng);
+    }
+}
+
+/// Strategy used to place the initial K-Means centroids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CentroidInit {
+    /// `k` distinct node positions sampled uniformly at random
+    #[default]
+    Random,
+
+    /// K-Means++: each new centroid is sampled with probability ∝ D², the squared
+    /// distance to the nearest centroid chosen so far
+    KMeansPlusPlus,
+
+    /// One random node, then repeatedly the node farthest from all chosen centroids
+    FarthestPoint,
+
+    /// Centers of a regular grid laid over the bounding box of the nodes
+    Grid,
+
+    /// Centroids of the previous fit; missing ones are added with K-Means++
+    WarmStart,
+}
+
+/// Per-node weight used by K-Means in its assignment and update steps.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NodeWeighting {
+    /// Every node counts equally (standard K-Means)
+    #[default]
+    Uniform,
+
+    /// Nodes are weighted by their residual energy at fit time
+    ResidualEnergy,
+
+    /// Caller-supplied weight per node id (missing entries count as 1.0)
+    Custom(Vec<f32>),
+}
+
 /// Simple K-Means clustering implementation for grouping sensor nodes by their 2D positions.
 ///
 /// Used to create spatial clusters (e.g. for zone-based or balanced cluster-head selection).
 ///
-/// This is a basic Lloyd's algorithm with random centroid initialization from existing points.
-pub(crate) struct KMeans {
+/// This is a basic Lloyd's algorithm with a selectable centroid initialization
+/// (`CentroidInit`); clusters that end up empty are reseeded with the point farthest
+/// from its centroid.
+///
+/// With a non-uniform `NodeWeighting`, centroids become weighted means and a node's
+/// distance to a centroid is divided by `sqrt(W_c / W̄)`, where `W_c` is the total weight
+/// of that cluster and `W̄` the mean over clusters — weight-rich clusters cover more
+/// ground, so centers drift toward energy-rich regions.
+///
+/// It can be fitted on a filtered view of the network (e.g. alive nodes only);
+/// assignments are always reported by original node id.
+pub struct KMeans {
     /// Number of clusters to form (k).
     n_clusters: usize,
 
     /// Current positions of the k cluster centroids.
     centroids: Vec<Vec2>,
 
-    /// Cluster assignment for each node: index i → cluster number (0..n_clusters-1).
-    clusters: Vec<usize>,
+    /// Cluster assignment for each node id: `Some(cluster)` for clustered nodes,
+    /// `None` for nodes left out of the fit.
+    clusters: Vec<Option<usize>>,
+
+    /// How the centroids are seeded at the start of each fit.
+    init: CentroidInit,
+
+    /// How nodes are weighted in the assignment and update steps.
+    weighting: NodeWeighting,
 }
 
 impl KMeans {
@@ -31,80 +123,264 @@ impl KMeans {
             n_clusters,
             centroids: Vec::new(),
             clusters: Vec::new(),
+            init: CentroidInit::default(),
+            weighting: NodeWeighting::default(),
         }
     }
 
-    /// Returns a reference to the current centroid positions.
-    /// (Only meaningful after calling `fit()`)
-    pub fn centroids(&self) -> &Vec<Vec2> {
-        &self.centroids
+    /// Selects the centroid initialization strategy.
+    pub fn with_init(mut self, init: CentroidInit) -> Self {
+        self.init = init;
+        self
     }
 
-    /// Returns a reference to the cluster assignment vector.
-    /// Length = number of nodes, value = assigned cluster index (0..n_clusters-1)
-    pub fn clusters(&self) -> &Vec<usize> {
-        &self.clusters
+    /// Selects how nodes are weighted (e.g. by residual energy).
+    pub fn with_weighting(mut self, weighting: NodeWeighting) -> Self {
+        self.weighting = weighting;
+        self
+    }
+
+    /// Weight of every node id under the configured `NodeWeighting`.
+    fn node_weights<S: NodeStore + ?Sized>(&self, wsn: &S) -> Vec<f32> {
+        match &self.weighting {
+            NodeWeighting::Uniform => vec![1.0; wsn.node_count()],
+            NodeWeighting::ResidualEnergy => (0..wsn.node_count())
+                .map(|node_id| wsn.remaining_energy_j(node_id).max(0.0))
+                .collect(),
+            NodeWeighting::Custom(weights) => (0..wsn.node_count())
+                .map(|node_id| weights.get(node_id).copied().unwrap_or(1.0).max(0.0))
+                .collect(),
+        }
     }
 
-    /// Recomputes each centroid as the mean position of all nodes assigned to it.
+    /// Computes the initial centroids for the nodes in `indices` according to `self.init`.
+    fn initial_centroids<S: NodeStore + ?Sized>(
+        &self,
+        wsn: &S,
+        indices: &[usize],
+        rng: &mut impl Rng,
+    ) -> Vec<Vec2> {
+        let k = self.n_clusters;
+
+        match self.init {
+            CentroidInit::Random => sample(rng, indices.len(), k)
+                .into_iter()
+                .map(|x| wsn.position(indices[x]))
+                .collect(),
+
+            CentroidInit::KMeansPlusPlus => {
+                let mut centroids = Vec::new();
+                kmeans_plus_plus(wsn, indices, &mut centroids, k, rng);
+                centroids
+            }
+
+            CentroidInit::FarthestPoint => {
+                let first = wsn.position(indices[rng.random_range(0..indices.len())]);
+                let mut centroids = vec![first];
+
+                while centroids.len() < k {
+                    let farthest = indices
+                        .iter()
+                        .map(|&node_id| wsn.position(node_id))
+                        .max_by(|&a, &b| {
+                            nearest_distance_sq(a, &centroids)
+                                .total_cmp(&nearest_distance_sq(b, &centroids))
+                        })
+                        .unwrap_or(first);
+                    centroids.push(farthest);
+                }
+
+                centroids
+            }
+
+            CentroidInit::Grid => {
+                let mut min = Vec2::splat(f32::INFINITY);
+                let mut max = Vec2::splat(f32::NEG_INFINITY);
+                for &node_id in indices {
+                    min = min.min(wsn.position(node_id));
+                    max = max.max(wsn.position(node_id));
+                }
+
+                let rows = (k as f32).sqrt().floor().max(1.0) as usize;
+                let cols = k.div_ceil(rows);
+                let cell = (max - min) / Vec2::new(cols as f32, rows as f32);
+
+                (0..k)
+                    .map(|i| {
+                        let (row, col) = (i / cols, i % cols);
+                        min + cell * Vec2::new(col as f32 + 0.5, row as f32 + 0.5)
+                    })
+                    .collect()
+            }
+
+            CentroidInit::WarmStart => {
+                let mut centroids = self.centroids.clone();
+                centroids.truncate(k);
+                kmeans_plus_plus(wsn, indices, &mut centroids, k, rng);
+                centroids
+            }
+        }
+    }
+
+    /// Recomputes each centroid as the (weighted) mean position of all nodes assigned to it.
+    /// Empty clusters are reseeded with the node farthest from its own centroid,
+    /// which is moved into the empty cluster.
     ///
-    /// Returns the previous centroid positions (used to check convergence).
-    fn update_centroids(&mut self, wsn: &[Node]) -> Vec<Vec2> {
-        let mut accum = vec![(Vec2::new(0.0, 0.0), 0usize); self.n_clusters];
+    /// Returns the previous centroid positions (used to check convergence) and the
+    /// total node weight of each cluster.
+    fn update_centroids(
+        &mut self,
+        positions: &[Vec2],
+        indices: &[usize],
+        weights: &[f32],
+    ) -> (Vec<Vec2>, Vec<f32>) {
+        let n_clusters = self.n_clusters;
         let previous_centroids = self.centroids.clone();
 
-        // Accumulate position sums and point counts per cluster
-        for (i, &c_id) in self.clusters.iter().enumerate() {
-            accum[c_id].0 += wsn[i].position;
-            accum[c_id].1 += 1;
+        // Accumulate weighted position sums and weights per cluster, chunk by chunk
+        let clusters = &self.clusters;
+        let partial_sums = parallel::map_chunks(indices, |chunk| {
+            let mut accum = vec![(Vec2::new(0.0, 0.0), 0.0f32); n_clusters];
+            for &node_id in chunk {
+                if let Some(c_id) = clusters[node_id] {
+                    accum[c_id].0 += positions[node_id] * weights[node_id];
+                    accum[c_id].1 += weights[node_id];
+                }
+            }
+            accum
+        });
+
+        // Combine the chunks in order so the result does not depend on threading
+        let mut accum = vec![(Vec2::new(0.0, 0.0), 0.0f32); n_clusters];
+        for partial in partial_sums {
+            for (total, (sum, weight)) in accum.iter_mut().zip(partial) {
+                total.0 += sum;
+                total.1 += weight;
+            }
         }
 
-        // Update centroids to mean position (skip empty clusters)
-        for (i, (sum, count)) in accum.into_iter().enumerate() {
-            if count > 0 {
-                self.centroids[i] = sum / count as f32;
+        // Update centroids to weighted mean position, remembering empty clusters
+        let mut empty_clusters = Vec::new();
+        let mut cluster_weights = vec![0.0; self.n_clusters];
+        for (i, (sum, weight)) in accum.into_iter().enumerate() {
+            if weight > 0.0 {
+                self.centroids[i] = sum / weight;
+                cluster_weights[i] = weight;
+            } else {
+                empty_clusters.push(i);
             }
         }
 
-        previous_centroids
+        // Reseed empty clusters with the worst-fitting nodes
+        for c_id in empty_clusters {
+            let farthest = indices
+                .iter()
+                .copied()
+                .filter_map(|node_id| {
+                    let assigned = self.clusters[node_id]?;
+                    let distance = (positions[node_id] - self.centroids[assigned]).length_squared();
+                    Some((node_id, distance))
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            if let Some((node_id, _)) = farthest {
+                self.centroids[c_id] = positions[node_id];
+                self.clusters[node_id] = Some(c_id);
+                cluster_weights[c_id] = weights[node_id];
+            }
+        }
+
+        (previous_centroids, cluster_weights)
     }
+}
 
-    /// Fits the K-Means model to the given list of sensor nodes.
+impl Clusterer for KMeans {
+    /// Changes the number of clusters used by the next fit
+    /// (keeps the current centroids for `CentroidInit::WarmStart`).
+    fn set_n_clusters(&mut self, n_clusters: usize) {
+        self.n_clusters = n_clusters;
+    }
+
+    /// Fits the K-Means model to the nodes listed in `indices` (ids into `wsn`).
     ///
     /// Steps:
-    /// 1. Randomly selects `n_clusters` distinct nodes as initial centroids.
+    /// 1. Seeds `n_clusters` centroids from the subset using the configured `CentroidInit`
+    ///    (k is capped at the subset size).
     /// 2. Iteratively:
-    ///    - Assigns each node to the nearest centroid
-    ///    - Updates centroids to mean of assigned nodes
+    ///    - Assigns each node to the nearest centroid (weight-scaled, see `NodeWeighting`)
+    ///    - Updates centroids to the (weighted) mean of assigned nodes
     /// 3. Stops after `MAX_ITER` iterations or when maximum centroid movement < `EPS`.
-    pub fn fit(&mut self, wsn: &Vec<Node>) {
-        let mut rng = rand::rng();
-
-        // Initialize centroids by randomly sampling n_clusters distinct node positions
-        self.centroids = sample(&mut rng, wsn.len(), self.n_clusters)
-            .into_iter()
-            .map(|x| wsn[x].position)
-            .collect();
+    ///
+    /// With the `parallel` feature both steps run on the thread pool; the result is
+    /// identical to the sequential one for the same `rng` state.
+    ///
+    /// Nodes outside the subset are reported as `None` by `clusters()`.
+    fn fit_indices(&mut self, wsn: &dyn NodeStore, indices: &[usize], mut rng: &mut dyn RngCore) {
+        self.n_clusters = self.n_clusters.min(indices.len());
 
         // Initialize cluster assignments (will be updated in loop)
-        self.clusters = vec![0; wsn.len()];
+        self.clusters = vec![None; wsn.node_count()];
+
+        if self.n_clusters == 0 {
+            self.centroids.clear();
+            return;
+        }
+
+        self.centroids = self.initial_centroids(wsn, indices, &mut rng);
+
+        let weights = self.node_weights(wsn);
+        let weighted = self.weighting != NodeWeighting::Uniform;
+
+        // Positions gathered once, so the iterations (and their threads) share a plain slice
+        let positions: Vec<Vec2> = (0..wsn.node_count()).map(|node_id| wsn.position(node_id)).collect();
+
+        // Distance scale per centroid (all 1.0 until cluster weights are known)
+        let mut distance_scale = vec![1.0f32; self.n_clusters];
 
         for _ in 0..MAX_ITER {
-            // Assignment step: find nearest centroid for each node
-            for i in 0..wsn.len() {
-                let mut min_dist = f32::INFINITY;
-
-                for (index, &centroid) in self.centroids.iter().enumerate() {
-                    let dist = (wsn[i].position - centroid).length();
-                    if dist < min_dist {
-                        min_dist = dist;
-                        self.clusters[i] = index;
+            // Assignment step: nearest centroid for each node (grid lookup when
+            // unweighted, linear scan over weight-scaled distances otherwise)
+            let centroids = &self.centroids;
+            let assignments: Vec<Option<usize>> = if !weighted {
+                let centroid_grid = SpatialGrid::new(centroids.iter().copied().enumerate());
+                parallel::map(indices, |&node_id| {
+                    centroid_grid.nearest(positions[node_id]).map(|(index, _)| index)
+                })
+            } else {
+                let distance_scale = &distance_scale;
+                parallel::map(indices, |&node_id| {
+                    let mut min_dist = f32::INFINITY;
+                    let mut nearest = None;
+
+                    for (index, &centroid) in centroids.iter().enumerate() {
+                        let dist = (positions[node_id] - centroid).length() * distance_scale[index];
+                        if dist < min_dist {
+                            min_dist = dist;
+                            nearest = Some(index);
+                        }
                     }
-                }
+
+                    nearest
+                })
+            };
+
+            for (&node_id, cluster) in indices.iter().zip(assignments) {
+                self.clusters[node_id] = cluster;
             }
 
             // Update step: recompute centroids and get previous positions
-            let prev_centroids = self.update_centroids(&wsn);
+            let (prev_centroids, cluster_weights) = self.update_centroids(&positions, indices, &weights);
+
+            if weighted {
+                let mean_weight = cluster_weights.iter().sum::<f32>() / self.n_clusters as f32;
+                for (scale, &cluster_weight) in distance_scale.iter_mut().zip(&cluster_weights) {
+                    *scale = if cluster_weight > 0.0 && mean_weight > 0.0 {
+                        (mean_weight / cluster_weight).sqrt()
+                    } else {
+                        1.0
+                    };
+                }
+            }
 
             // Convergence check: maximum distance any centroid moved
             let mut max_shift: f32 = 0.0;
@@ -117,4 +393,451 @@ impl KMeans {
             }
         }
     }
+
+    /// Returns the current centroid positions.
+    fn centers(&self) -> &[Vec2] {
+        &self.centroids
+    }
+
+    /// Returns the cluster assignment vector.
+    /// Length = number of nodes, value = assigned cluster index (0..n_clusters-1),
+    /// or `None` if the node was not part of the fitted subset.
+    fn clusters(&self) -> &[Option<usize>] {
+        &self.clusters
+    }
+}
+
+/// Quality indicators of one round's cluster structure.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClusterQuality {
+    /// Number of clusters (one per cluster head)
+    pub cluster_count: usize,
+
+    /// Sum of squared distances from each node to its cluster's centroid (m²)
+    pub sse: f32,
+
+    /// Mean silhouette coefficient in `[-1, 1]` (higher → better separated clusters)
+    pub silhouette: f32,
+
+    /// Jain's fairness index of the cluster sizes, `(Σs)² / (k·Σs²)` in `(0, 1]`
+    /// (1 → all clusters equally large)
+    pub size_balance_index: f32,
+
+    /// Mean member → CH distance (meters)
+    pub mean_member_distance_m: f32,
+
+    /// Maximum member → CH distance (meters)
+    pub max_member_distance_m: f32,
+
+    /// Clusters without any member node
+    pub empty_clusters: usize,
+}
+
+impl ClusterQuality {
+    /// Evaluates the cluster structure currently recorded on the nodes: every alive
+    /// cluster head defines a cluster made of itself and its `cluster_member_ids`.
+    pub fn from_cluster_heads<S: NodeStore + ?Sized>(wsn: &S) -> Self {
+        let cluster_head_ids: Vec<usize> = (0..wsn.node_count())
+            .filter(|&id| wsn.is_cluster_head(id) && wsn.is_alive(id))
+            .collect();
+
+        if cluster_head_ids.is_empty() {
+            return Self::default();
+        }
+
+        let mut clusters: Vec<Option<usize>> = vec![None; wsn.node_count()];
+        let mut sizes = Vec::with_capacity(cluster_head_ids.len());
+        let mut centroids = Vec::with_capacity(cluster_head_ids.len());
+        let mut member_distance_sum = 0.0;
+        let mut member_count = 0usize;
+        let mut max_member_distance_m: f32 = 0.0;
+        let mut empty_clusters = 0;
+
+        for (cluster, &ch_id) in cluster_head_ids.iter().enumerate() {
+            let ch_position = wsn.position(ch_id);
+            let member_ids = wsn.cluster_member_ids(ch_id);
+            clusters[ch_id] = Some(cluster);
+
+            let mut position_sum = ch_position;
+            for &member_id in member_ids {
+                clusters[member_id] = Some(cluster);
+                position_sum += wsn.position(member_id);
+
+                let distance = (wsn.position(member_id) - ch_position).length();
+                member_distance_sum += distance;
+                max_member_distance_m = max_member_distance_m.max(distance);
+            }
+
+            let size = member_ids.len() + 1;
+            member_count += member_ids.len();
+            if member_ids.is_empty() {
+                empty_clusters += 1;
+            }
+
+            sizes.push(size as f32);
+            centroids.push(position_sum / size as f32);
+        }
+
+        let size_sum: f32 = sizes.iter().sum();
+        let size_sq_sum: f32 = sizes.iter().map(|s| s * s).sum();
+
+        Self {
+            cluster_count: cluster_head_ids.len(),
+            sse: sum_of_squared_errors(wsn, &clusters, &centroids),
+            silhouette: silhouette_score(wsn, &clusters),
+            size_balance_index: size_sum * size_sum / (sizes.len() as f32 * size_sq_sum),
+            mean_member_distance_m: if member_count > 0 {
+                member_distance_sum / member_count as f32
+            } else {
+                0.0
+            },
+            max_member_distance_m,
+            empty_clusters,
+        }
+    }
+}
+
+/// Sum of squared distances from every clustered node to its cluster center (SSE / inertia).
+pub fn sum_of_squared_errors<S: NodeStore + ?Sized>(
+    wsn: &S,
+    clusters: &[Option<usize>],
+    centers: &[Vec2],
+) -> f32 {
+    clusters
+        .iter()
+        .enumerate()
+        .take(wsn.node_count())
+        .filter_map(|(id, &cluster)| {
+            cluster.map(|c| (wsn.position(id) - centers[c]).length_squared())
+        })
+        .sum()
+}
+
+/// Mean silhouette coefficient over all clustered nodes, in `[-1, 1]`.
+///
+/// For node i: `s = (b - a) / max(a, b)` with `a` the mean distance to its own
+/// cluster and `b` the smallest mean distance to another cluster. Nodes in
+/// singleton clusters score 0. Returns 0 when fewer than two clusters exist.
+/// Runs in O(n²).
+pub fn silhouette_score<S: NodeStore + ?Sized>(wsn: &S, clusters: &[Option<usize>]) -> f32 {
+    let clustered: Vec<(Vec2, usize)> = clusters
+        .iter()
+        .enumerate()
+        .take(wsn.node_count())
+        .filter_map(|(id, &cluster)| cluster.map(|c| (wsn.position(id), c)))
+        .collect();
+
+    let n_clusters = clustered.iter().map(|&(_, c)| c + 1).max().unwrap_or(0);
+    if n_clusters < 2 {
+        return 0.0;
+    }
+
+    let mut sizes = vec![0usize; n_clusters];
+    for &(_, c) in &clustered {
+        sizes[c] += 1;
+    }
+
+    let mut total = 0.0;
+    for &(position, own) in &clustered {
+        if sizes[own] <= 1 {
+            continue;
+        }
+
+        let mut distance_sums = vec![0.0f32; n_clusters];
+        for &(other_position, other) in &clustered {
+            distance_sums[other] += (position - other_position).length();
+        }
+
+        let a = distance_sums[own] / (sizes[own] - 1) as f32;
+        let b = (0..n_clusters)
+            .filter(|&c| c != own && sizes[c] > 0)
+            .map(|c| distance_sums[c] / sizes[c] as f32)
+            .fold(f32::INFINITY, f32::min);
+
+        if b.is_finite() && a.max(b) > 0.0 {
+            total += (b - a) / a.max(b);
+        }
+    }
+
+    total / clustered.len() as f32
+}
+
+/// Squared distance from `point` to the nearest of `centroids`.
+fn nearest_distance_sq(point: Vec2, centroids: &[Vec2]) -> f32 {
+    centroids
+        .iter()
+        .map(|&c| (point - c).length_squared())
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Index (into `centers`) of the center nearest to `point`.
+fn nearest_center(point: Vec2, centers: &[Vec2]) -> usize {
+    let mut nearest = 0;
+    let mut min_dist = f32::INFINITY;
+
+    for (index, &center) in centers.iter().enumerate() {
+        let dist = (point - center).length_squared();
+        if dist < min_dist {
+            min_dist = dist;
+            nearest = index;
+        }
+    }
+
+    nearest
+}
+
+/// Extends `centroids` to `k` entries with K-Means++ D² sampling over the nodes in `indices`.
+///
+/// Returns the ids of the nodes whose positions were added.
+fn kmeans_plus_plus<S: NodeStore + ?Sized>(
+    wsn: &S,
+    indices: &[usize],
+    centroids: &mut Vec<Vec2>,
+    k: usize,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let mut chosen_ids = Vec::new();
+
+    if centroids.is_empty() && k > 0 {
+        let first = indices[rng.random_range(0..indices.len())];
+        centroids.push(wsn.position(first));
+        chosen_ids.push(first);
+    }
+
+    while centroids.len() < k {
+        let weights: Vec<f32> = indices
+            .iter()
+            .map(|&node_id| nearest_distance_sq(wsn.position(node_id), centroids))
+            .collect();
+        let total: f32 = weights.iter().sum();
+
+        // All points already coincide with a centroid → any point will do
+        if total <= 0.0 {
+            let any = indices[rng.random_range(0..indices.len())];
+            centroids.push(wsn.position(any));
+            chosen_ids.push(any);
+            continue;
+        }
+
+        let mut target = rng.random::<f32>() * total;
+        let mut chosen = indices[indices.len() - 1];
+        for (&node_id, &weight) in indices.iter().zip(&weights) {
+            if target < weight {
+                chosen = node_id;
+                break;
+            }
+            target -= weight;
+        }
+
+        centroids.push(wsn.position(chosen));
+        chosen_ids.push(chosen);
+    }
+
+    chosen_ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    /// `count` nodes scattered over a 200 m × 200 m field, with a dense patch near
+    /// the origin so that D² sampling mostly favours the sparse nodes.
+    fn scattered_nodes(count: usize, rng: &mut StdRng) -> Vec<Node> {
+        (0..count)
+            .map(|id| {
+                let side = if id % 2 == 0 { 5.0 } else { 200.0 };
+                Node::new(id, Vec2::new(rng.random_range(0.0..side), rng.random_range(0.0..side)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kmeans_plus_plus_picks_distinct_nodes() {
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let nodes = scattered_nodes(40, &mut rng);
+            let indices: Vec<usize> = (0..nodes.len()).collect();
+
+            for k in [1, 8, 40] {
+                let mut centroids = Vec::new();
+                let ids = kmeans_plus_plus(&nodes, &indices, &mut centroids, k, &mut rng);
+
+                assert_eq!(ids.len(), k);
+                assert_eq!(ids.iter().collect::<HashSet<_>>().len(), k, "seed {seed}, k {k}");
+                assert!(ids.iter().zip(&centroids).all(|(&id, &c)| nodes[id].position == c));
+            }
+        }
+    }
+
+    #[test]
+    fn farthest_point_picks_distinct_nodes() {
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let nodes = scattered_nodes(40, &mut rng);
+            let indices: Vec<usize> = (0..nodes.len()).collect();
+
+            for k in [1, 8, 40] {
+                let kmeans = KMeans::new(k).with_init(CentroidInit::FarthestPoint);
+                let centroids = kmeans.initial_centroids(&nodes, &indices, &mut rng);
+
+                let ids: HashSet<usize> = centroids
+                    .iter()
+                    .map(|&c| nodes.iter().position(|node| node.position == c).unwrap())
+                    .collect();
+                assert_eq!(ids.len(), k, "seed {seed}, k {k}");
+            }
+        }
+    }
+
+    /// Three well-separated blobs of 10 nodes each; node `id` belongs to blob `id / 10`.
+    pub(super) fn three_blobs(rng: &mut StdRng) -> Vec<Node> {
+        let blob_centers = [Vec2::new(20.0, 20.0), Vec2::new(150.0, 20.0), Vec2::new(80.0, 150.0)];
+        (0..30)
+            .map(|id| {
+                let jitter = Vec2::new(rng.random_range(-3.0..3.0), rng.random_range(-3.0..3.0));
+                Node::new(id, blob_centers[id / 10] + jitter)
+            })
+            .collect()
+    }
+
+    /// Asserts that `clusters` groups the 30 nodes of `three_blobs` by blob.
+    pub(super) fn assert_blobs_recovered(clusters: &[Option<usize>]) {
+        let blob_clusters = [0, 10, 20].map(|first_id| clusters[first_id].unwrap());
+        for (id, cluster) in clusters.iter().enumerate().take(30) {
+            assert_eq!(*cluster, Some(blob_clusters[id / 10]), "node {id}");
+        }
+        assert_eq!(blob_clusters.iter().collect::<HashSet<_>>().len(), 3);
+    }
+
+    #[test]
+    fn fit_alive_leaves_dead_nodes_unclustered() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut nodes = three_blobs(&mut rng);
+        for id in (0..30).step_by(3) {
+            nodes[id].is_alive = false;
+        }
+
+        let mut kmeans = KMeans::new(3).with_init(CentroidInit::KMeansPlusPlus);
+        kmeans.fit_alive(&nodes, &mut rng);
+        let clusters = kmeans.clusters();
+
+        assert_eq!(clusters.len(), 30);
+        for (id, cluster) in clusters.iter().enumerate() {
+            assert_eq!(cluster.is_some(), nodes[id].is_alive, "node {id}");
+        }
+
+        // Alive nodes are grouped by blob, by their original ids
+        let blob_cluster = |blob: usize| clusters[blob * 10..].iter().flatten().copied().next().unwrap();
+        for id in (0..30).filter(|&id| nodes[id].is_alive) {
+            assert_eq!(clusters[id], Some(blob_cluster(id / 10)), "node {id}");
+        }
+        assert_eq!([0, 1, 2].map(blob_cluster).iter().collect::<HashSet<_>>().len(), 3);
+    }
+
+    #[test]
+    fn cluster_count_is_capped_at_alive_nodes() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut nodes = three_blobs(&mut rng);
+        for node in nodes.iter_mut().filter(|node| node.id != 3 && node.id != 25) {
+            node.is_alive = false;
+        }
+
+        let mut kmeans = KMeans::new(5);
+        kmeans.fit_alive(&nodes, &mut rng);
+
+        assert_eq!(kmeans.centers().len(), 2);
+        assert_ne!(kmeans.clusters()[3], kmeans.clusters()[25]);
+        assert_eq!(kmeans.clusters().iter().flatten().count(), 2);
+    }
+
+    #[test]
+    fn weighted_centroid_is_the_weighted_mean() {
+        let nodes: Vec<Node> = [0.0, 10.0, 40.0]
+            .iter()
+            .enumerate()
+            .map(|(id, &x)| Node::new(id, Vec2::new(x, 0.0)))
+            .collect();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut kmeans = KMeans::new(1).with_weighting(NodeWeighting::Custom(vec![3.0, 1.0]));
+        kmeans.fit_alive(&nodes, &mut rng);
+        assert!((kmeans.centers()[0] - Vec2::new(10.0, 0.0)).length() < 1e-4);
+
+        let mut energy_nodes = nodes.clone();
+        energy_nodes[2].remaining_energy_j = 0.0;
+        let mut kmeans = KMeans::new(1).with_weighting(NodeWeighting::ResidualEnergy);
+        kmeans.fit_alive(&energy_nodes, &mut rng);
+        assert!((kmeans.centers()[0] - Vec2::new(5.0, 0.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn energy_rich_clusters_cover_more_nodes() {
+        // 101 nodes 1 m apart on a line; the left half holds 10× more energy
+        let nodes: Vec<Node> = (0..=100)
+            .map(|id| {
+                let mut node = Node::new(id, Vec2::new(id as f32, 0.0));
+                node.remaining_energy_j = if id < 50 { 2.0 } else { 0.2 };
+                node
+            })
+            .collect();
+        let left_cluster_size = |weighting: NodeWeighting| {
+            let mut kmeans = KMeans::new(2).with_init(CentroidInit::Grid).with_weighting(weighting);
+            kmeans.fit_alive(&nodes, &mut StdRng::seed_from_u64(0));
+            let left = kmeans.clusters()[0];
+            kmeans.clusters().iter().filter(|&&cluster| cluster == left).count()
+        };
+
+        let uniform = left_cluster_size(NodeWeighting::Uniform);
+        let weighted = left_cluster_size(NodeWeighting::ResidualEnergy);
+        assert!((50..=51).contains(&uniform));
+        assert!(weighted > uniform, "uniform {uniform}, weighted {weighted}");
+    }
+
+    #[test]
+    fn cluster_quality_of_recorded_clusters() {
+        // CH 0 with members 1 and 2, CH 3 alone, node 4 a dead CH
+        let mut nodes: Vec<Node> = [(0.0, 0.0), (3.0, 0.0), (0.0, 4.0), (100.0, 0.0), (50.0, 50.0)]
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| Node::new(id, Vec2::new(x, y)))
+            .collect();
+        for ch_id in [0, 3, 4] {
+            nodes.promote_to_cluster_head(ch_id, 1);
+        }
+        nodes.join_cluster(1, 0);
+        nodes.join_cluster(2, 0);
+        nodes.finish_cluster_formation();
+        nodes[4].is_alive = false;
+
+        let quality = ClusterQuality::from_cluster_heads(&nodes);
+
+        assert_eq!(quality.cluster_count, 2);
+        assert_eq!(quality.empty_clusters, 1);
+        // Centroid (1, 4/3): 25/9 + 52/9 + 73/9
+        assert!((quality.sse - 150.0 / 9.0).abs() < 1e-4);
+        // Sizes 3 and 1: 4² / (2 · 10)
+        assert!((quality.size_balance_index - 0.8).abs() < 1e-6);
+        assert!((quality.mean_member_distance_m - 3.5).abs() < 1e-6);
+        assert_eq!(quality.max_member_distance_m, 4.0);
+        // Three well-separated nodes score ~0.96, the singleton CH scores 0
+        assert!(quality.silhouette > 0.71 && quality.silhouette < 0.73);
+    }
+
+    #[test]
+    fn silhouette_needs_two_clusters() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let nodes = three_blobs(&mut rng);
+
+        assert_eq!(silhouette_score(&nodes, &[Some(0); 30]), 0.0);
+        assert_eq!(silhouette_score(&nodes, &[None; 30]), 0.0);
+
+        let by_blob: Vec<Option<usize>> = (0..30).map(|id| Some(id / 10)).collect();
+        let shuffled: Vec<Option<usize>> = (0..30).map(|id| Some(id % 3)).collect();
+        assert!(silhouette_score(&nodes, &by_blob) > 0.9);
+        assert!(silhouette_score(&nodes, &shuffled) < 0.0);
+    }
 }
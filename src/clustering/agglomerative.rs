@@ -0,0 +1,204 @@
+use super::Clusterer;
+use crate::columns::NodeStore;
+use glam::Vec2;
+use rand::RngCore;
+
+/// Inter-cluster distance used when merging clusters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+    /// Distance between the two closest members
+    Single,
+
+    /// Distance between the two farthest members
+    Complete,
+
+    /// Mean distance over all member pairs
+    Average,
+
+    /// Increase in within-cluster sum of squares (Ward's method)
+    Ward,
+}
+
+/// Bottom-up (agglomerative) hierarchical clustering.
+///
+/// Starts with one cluster per node and repeatedly merges the closest pair under
+/// the chosen `Linkage` until `n_clusters` remain. Distances are updated with the
+/// Lance–Williams formula on a dense matrix: O(n²) memory, O(n³) time.
+pub struct Agglomerative {
+    /// Number of clusters to stop at (k).
+    n_clusters: usize,
+
+    /// Linkage criterion.
+    linkage: Linkage,
+
+    /// Centroid of each final cluster.
+    centers: Vec<Vec2>,
+
+    /// Cluster assignment for each node id (`None` → not in the fitted subset).
+    clusters: Vec<Option<usize>>,
+}
+
+impl Agglomerative {
+    /// Creates a new (unfitted) agglomerative clusterer.
+    pub fn new(n_clusters: usize, linkage: Linkage) -> Self {
+        Self {
+            n_clusters,
+            linkage,
+            centers: Vec::new(),
+            clusters: Vec::new(),
+        }
+    }
+
+    /// Lance–Williams update: distance from the merge of `i` and `j` to cluster `m`.
+    fn merged_distance(&self, d_im: f32, d_jm: f32, d_ij: f32, n_i: f32, n_j: f32, n_m: f32) -> f32 {
+        match self.linkage {
+            Linkage::Single => d_im.min(d_jm),
+            Linkage::Complete => d_im.max(d_jm),
+            Linkage::Average => (n_i * d_im + n_j * d_jm) / (n_i + n_j),
+            Linkage::Ward => {
+                ((n_i + n_m) * d_im + (n_j + n_m) * d_jm - n_m * d_ij) / (n_i + n_j + n_m)
+            }
+        }
+    }
+}
+
+impl Clusterer for Agglomerative {
+    fn set_n_clusters(&mut self, n_clusters: usize) {
+        self.n_clusters = n_clusters;
+    }
+
+    fn fit_indices(&mut self, wsn: &dyn NodeStore, indices: &[usize], _rng: &mut dyn RngCore) {
+        let n = indices.len();
+        let k = self.n_clusters.min(n);
+
+        self.clusters = vec![None; wsn.node_count()];
+        self.centers.clear();
+
+        if k == 0 {
+            return;
+        }
+
+        // Pairwise distances (squared for Ward, as Lance–Williams requires)
+        let mut dist = vec![0.0f32; n * n];
+        for a in 0..n {
+            for b in (a + 1)..n {
+                let diff = wsn.position(indices[a]) - wsn.position(indices[b]);
+                let d = match self.linkage {
+                    Linkage::Ward => diff.length_squared(),
+                    _ => diff.length(),
+                };
+                dist[a * n + b] = d;
+                dist[b * n + a] = d;
+            }
+        }
+
+        // label[p] = representative cluster of point p
+        let mut label: Vec<usize> = (0..n).collect();
+        let mut size = vec![1.0f32; n];
+        let mut active = vec![true; n];
+
+        for _ in 0..(n - k) {
+            // Closest pair of active clusters
+            let mut best = (0, 0);
+            let mut best_dist = f32::INFINITY;
+            for a in (0..n).filter(|&a| active[a]) {
+                for b in ((a + 1)..n).filter(|&b| active[b]) {
+                    if dist[a * n + b] < best_dist {
+                        best_dist = dist[a * n + b];
+                        best = (a, b);
+                    }
+                }
+            }
+
+            // Merge j into i
+            let (i, j) = best;
+            for m in (0..n).filter(|&m| active[m] && m != i && m != j) {
+                let d = self.merged_distance(
+                    dist[i * n + m],
+                    dist[j * n + m],
+                    dist[i * n + j],
+                    size[i],
+                    size[j],
+                    size[m],
+                );
+                dist[i * n + m] = d;
+                dist[m * n + i] = d;
+            }
+
+            size[i] += size[j];
+            active[j] = false;
+            for l in label.iter_mut().filter(|l| **l == j) {
+                *l = i;
+            }
+        }
+
+        // Renumber remaining clusters 0..k and compute centroids
+        let mut cluster_of_representative = vec![usize::MAX; n];
+        for (cluster, representative) in (0..n).filter(|&r| active[r]).enumerate() {
+            cluster_of_representative[representative] = cluster;
+        }
+
+        let mut accum = vec![(Vec2::ZERO, 0usize); k];
+        for (p, &representative) in label.iter().enumerate() {
+            let cluster = cluster_of_representative[representative];
+            let node_id = indices[p];
+            self.clusters[node_id] = Some(cluster);
+            accum[cluster].0 += wsn.position(node_id);
+            accum[cluster].1 += 1;
+        }
+
+        self.centers = accum
+            .into_iter()
+            .map(|(sum, count)| sum / count as f32)
+            .collect();
+    }
+
+    fn centers(&self) -> &[Vec2] {
+        &self.centers
+    }
+
+    fn clusters(&self) -> &[Option<usize>] {
+        &self.clusters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::tests::{assert_blobs_recovered, three_blobs};
+    use crate::node::Node;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn every_linkage_recovers_separated_blobs() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let nodes = three_blobs(&mut rng);
+
+        for linkage in [Linkage::Single, Linkage::Complete, Linkage::Average, Linkage::Ward] {
+            let mut agglomerative = Agglomerative::new(3, linkage);
+            agglomerative.fit_alive(&nodes, &mut rng);
+
+            assert_eq!(agglomerative.centers().len(), 3, "{linkage:?}");
+            assert_blobs_recovered(agglomerative.clusters());
+        }
+    }
+
+    #[test]
+    fn single_linkage_chains_along_a_line() {
+        // Gaps of 1, 1, 5, 1, 1 m: single linkage cuts at the widest gap
+        let nodes: Vec<Node> = [0.0, 1.0, 2.0, 7.0, 8.0, 9.0]
+            .iter()
+            .enumerate()
+            .map(|(id, &x)| Node::new(id, Vec2::new(x, 0.0)))
+            .collect();
+
+        let mut agglomerative = Agglomerative::new(2, Linkage::Single);
+        agglomerative.fit_alive(&nodes, &mut StdRng::seed_from_u64(0));
+        let clusters = agglomerative.clusters();
+
+        assert!(clusters[..3].iter().all(|&cluster| cluster == clusters[0]));
+        assert!(clusters[3..].iter().all(|&cluster| cluster == clusters[3]));
+        assert_ne!(clusters[0], clusters[3]);
+    }
+}
@@ -0,0 +1,148 @@
+use super::{kmeans_plus_plus, Clusterer, EPS, MAX_ITER};
+use crate::columns::NodeStore;
+use crate::config::BALANCED_KMEANS_CAPACITY_SLACK;
+use glam::Vec2;
+use rand::RngCore;
+
+/// Size-constrained (balanced) K-Means.
+///
+/// Each cluster may hold at most `ceil(n / k) · capacity_slack` nodes. The assignment
+/// step is greedy: all (node, cluster) pairs are visited in order of increasing
+/// distance and a node joins the first cluster that still has room.
+pub struct BalancedKMeans {
+    /// Number of clusters to form (k).
+    n_clusters: usize,
+
+    /// Capacity multiplier over the perfectly balanced cluster size (≥ 1.0).
+    capacity_slack: f32,
+
+    /// Current centroid of each cluster.
+    centers: Vec<Vec2>,
+
+    /// Cluster assignment for each node id (`None` → not in the fitted subset).
+    clusters: Vec<Option<usize>>,
+}
+
+impl BalancedKMeans {
+    /// Creates a new (unfitted) balanced K-Means instance with the default capacity slack.
+    pub fn new(n_clusters: usize) -> Self {
+        Self {
+            n_clusters,
+            capacity_slack: BALANCED_KMEANS_CAPACITY_SLACK,
+            centers: Vec::new(),
+            clusters: Vec::new(),
+        }
+    }
+
+    /// Sets the capacity multiplier (clamped to at least 1.0 so every node fits).
+    pub fn with_capacity_slack(mut self, capacity_slack: f32) -> Self {
+        self.capacity_slack = capacity_slack.max(1.0);
+        self
+    }
+
+    /// Greedy capacity-constrained assignment of the nodes in `indices`.
+    fn assign(&mut self, wsn: &dyn NodeStore, indices: &[usize], capacity: usize) {
+        let mut pairs: Vec<(f32, usize, usize)> = Vec::with_capacity(indices.len() * self.centers.len());
+        for &node_id in indices {
+            for (cluster, &center) in self.centers.iter().enumerate() {
+                pairs.push(((wsn.position(node_id) - center).length_squared(), node_id, cluster));
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut load = vec![0usize; self.centers.len()];
+        let mut assigned = vec![false; wsn.node_count()];
+
+        for (_, node_id, cluster) in pairs {
+            if !assigned[node_id] && load[cluster] < capacity {
+                assigned[node_id] = true;
+                load[cluster] += 1;
+                self.clusters[node_id] = Some(cluster);
+            }
+        }
+    }
+}
+
+impl Clusterer for BalancedKMeans {
+    fn set_n_clusters(&mut self, n_clusters: usize) {
+        self.n_clusters = n_clusters;
+    }
+
+    /// K-Means++ seeding, then alternating greedy balanced assignment and centroid
+    /// updates until the centroids move less than `EPS` or `MAX_ITER` is reached.
+    fn fit_indices(&mut self, wsn: &dyn NodeStore, indices: &[usize], mut rng: &mut dyn RngCore) {
+        let k = self.n_clusters.min(indices.len());
+
+        self.clusters = vec![None; wsn.node_count()];
+        self.centers.clear();
+
+        if k == 0 {
+            return;
+        }
+
+        let balanced_size = indices.len().div_ceil(k) as f32;
+        let capacity = (balanced_size * self.capacity_slack).ceil() as usize;
+
+        kmeans_plus_plus(wsn, indices, &mut self.centers, k, &mut rng);
+
+        for _ in 0..MAX_ITER {
+            self.assign(wsn, indices, capacity);
+
+            let mut accum = vec![(Vec2::ZERO, 0usize); k];
+            for &node_id in indices {
+                if let Some(cluster) = self.clusters[node_id] {
+                    accum[cluster].0 += wsn.position(node_id);
+                    accum[cluster].1 += 1;
+                }
+            }
+
+            let mut max_shift: f32 = 0.0;
+            for (center, (sum, count)) in self.centers.iter_mut().zip(accum) {
+                if count > 0 {
+                    let updated = sum / count as f32;
+                    max_shift = max_shift.max((updated - *center).length());
+                    *center = updated;
+                }
+            }
+
+            if max_shift < EPS {
+                break;
+            }
+        }
+    }
+
+    fn centers(&self) -> &[Vec2] {
+        &self.centers
+    }
+
+    fn clusters(&self) -> &[Option<usize>] {
+        &self.clusters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::tests::three_blobs;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn clusters_respect_the_capacity() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let nodes = three_blobs(&mut rng);
+
+        // ceil(30 / 2) · slack nodes per cluster at most
+        for (slack, capacity) in [(1.0, 15), (1.2, 18)] {
+            let mut balanced = BalancedKMeans::new(2).with_capacity_slack(slack);
+            balanced.fit_alive(&nodes, &mut rng);
+
+            let mut sizes = [0; 2];
+            for cluster in balanced.clusters().iter().flatten() {
+                sizes[*cluster] += 1;
+            }
+            assert_eq!(sizes.iter().sum::<usize>(), 30);
+            assert!(sizes.iter().all(|&size| size <= capacity), "slack {slack}: {sizes:?}");
+        }
+    }
+}
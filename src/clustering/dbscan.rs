@@ -0,0 +1,173 @@
+use super::Clusterer;
+use crate::columns::NodeStore;
+use crate::config::{DBSCAN_EPS_M, DBSCAN_MIN_POINTS};
+use crate::spatial::SpatialGrid;
+use glam::Vec2;
+use rand::RngCore;
+use std::collections::VecDeque;
+
+/// DBSCAN density-based clustering.
+///
+/// The number of clusters follows from the node density (`set_n_clusters` is ignored).
+/// Noise nodes are left unclustered (`None`); cluster centers are member centroids.
+pub struct Dbscan {
+    /// Neighbourhood radius (meters).
+    eps_m: f32,
+
+    /// Minimum neighbourhood size (including the node itself) of a core point.
+    min_points: usize,
+
+    /// Centroid of each discovered cluster.
+    centers: Vec<Vec2>,
+
+    /// Cluster assignment for each node id (`None` → noise or not in the subset).
+    clusters: Vec<Option<usize>>,
+}
+
+impl Dbscan {
+    /// Creates a DBSCAN instance with the given radius and core-point threshold.
+    pub fn new(eps_m: f32, min_points: usize) -> Self {
+        Self {
+            eps_m,
+            min_points,
+            centers: Vec::new(),
+            clusters: Vec::new(),
+        }
+    }
+
+    /// Positions in `indices` (as positions into `indices`) within `eps_m` of point `i`;
+    /// `grid` indexes the points of `indices` by their position in it.
+    fn region_query(&self, wsn: &dyn NodeStore, grid: &SpatialGrid, indices: &[usize], i: usize) -> Vec<usize> {
+        grid.within_radius(wsn.position(indices[i]), self.eps_m)
+            .into_iter()
+            .map(|(j, _)| j)
+            .collect()
+    }
+}
+
+impl Default for Dbscan {
+    fn default() -> Self {
+        Dbscan::new(DBSCAN_EPS_M, DBSCAN_MIN_POINTS)
+    }
+}
+
+impl Clusterer for Dbscan {
+    fn set_n_clusters(&mut self, _n_clusters: usize) {}
+
+    /// Classic DBSCAN: every unvisited core point starts a new cluster that is
+    /// expanded breadth-first through density-reachable points.
+    fn fit_indices(&mut self, wsn: &dyn NodeStore, indices: &[usize], _rng: &mut dyn RngCore) {
+        let mut labels: Vec<Option<usize>> = vec![None; indices.len()];
+        let mut visited = vec![false; indices.len()];
+        let mut n_clusters = 0;
+
+        // Cells of `eps_m` keep every radius query to the 3×3 cells around the point
+        let grid = SpatialGrid::with_cell_size(
+            indices.iter().enumerate().map(|(i, &id)| (i, wsn.position(id))),
+            self.eps_m,
+        );
+
+        for i in 0..indices.len() {
+            if visited[i] {
+                continue;
+            }
+            visited[i] = true;
+
+            let neighbours = self.region_query(wsn, &grid, indices, i);
+            if neighbours.len() < self.min_points {
+                continue; // noise (may still become a border point later)
+            }
+
+            let cluster = n_clusters;
+            n_clusters += 1;
+            labels[i] = Some(cluster);
+
+            let mut queue: VecDeque<usize> = neighbours.into();
+            while let Some(j) = queue.pop_front() {
+                if labels[j].is_none() {
+                    labels[j] = Some(cluster);
+                }
+                if visited[j] {
+                    continue;
+                }
+                visited[j] = true;
+
+                let expansion = self.region_query(wsn, &grid, indices, j);
+                if expansion.len() >= self.min_points {
+                    queue.extend(expansion);
+                }
+            }
+        }
+
+        // Map labels back to node ids and compute cluster centroids
+        self.clusters = vec![None; wsn.node_count()];
+        let mut accum = vec![(Vec2::ZERO, 0usize); n_clusters];
+
+        for (i, &label) in labels.iter().enumerate() {
+            if let Some(cluster) = label {
+                let node_id = indices[i];
+                self.clusters[node_id] = Some(cluster);
+                accum[cluster].0 += wsn.position(node_id);
+                accum[cluster].1 += 1;
+            }
+        }
+
+        self.centers = accum
+            .into_iter()
+            .map(|(sum, count)| sum / count as f32)
+            .collect();
+    }
+
+    fn centers(&self) -> &[Vec2] {
+        &self.centers
+    }
+
+    fn clusters(&self) -> &[Option<usize>] {
+        &self.clusters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::tests::{assert_blobs_recovered, three_blobs};
+    use crate::node::Node;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn dense_blobs_become_clusters_and_outliers_noise() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut nodes = three_blobs(&mut rng);
+        nodes.push(Node::new(30, Vec2::new(190.0, 190.0)));
+        nodes.push(Node::new(31, Vec2::new(195.0, 190.0)));
+
+        let mut dbscan = Dbscan::new(10.0, 3);
+        dbscan.fit_alive(&nodes, &mut rng);
+
+        assert_eq!(dbscan.centers().len(), 3);
+        assert_blobs_recovered(dbscan.clusters());
+        assert_eq!(dbscan.clusters()[30..], [None, None]);
+
+        // Centers are member centroids
+        for (cluster, &center) in dbscan.centers().iter().enumerate() {
+            let members: Vec<Vec2> = (0..30)
+                .filter(|&id| dbscan.clusters()[id] == Some(cluster))
+                .map(|id| nodes[id].position)
+                .collect();
+            let centroid = members.iter().sum::<Vec2>() / members.len() as f32;
+            assert!((center - centroid).length() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn sparse_network_is_all_noise() {
+        let nodes: Vec<Node> = (0..5).map(|id| Node::new(id, Vec2::new(id as f32 * 50.0, 0.0))).collect();
+
+        let mut dbscan = Dbscan::new(10.0, 2);
+        dbscan.fit_alive(&nodes, &mut StdRng::seed_from_u64(0));
+
+        assert!(dbscan.centers().is_empty());
+        assert!(dbscan.clusters().iter().all(Option::is_none));
+    }
+}
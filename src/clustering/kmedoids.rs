@@ -0,0 +1,142 @@
+use super::{kmeans_plus_plus, nearest_center, Clusterer, MAX_ITER};
+use crate::columns::NodeStore;
+use glam::Vec2;
+use rand::RngCore;
+
+/// K-Medoids clustering (alternating / Voronoi-iteration variant of PAM).
+///
+/// Every cluster center is the position of a real node: the member that minimizes
+/// the total distance to all other members of its cluster.
+pub struct KMedoids {
+    /// Number of clusters to form (k).
+    n_clusters: usize,
+
+    /// Node ids of the current medoids.
+    medoid_ids: Vec<usize>,
+
+    /// Positions of the current medoids.
+    centers: Vec<Vec2>,
+
+    /// Cluster assignment for each node id (`None` → not in the fitted subset).
+    clusters: Vec<Option<usize>>,
+}
+
+impl KMedoids {
+    /// Creates a new (unfitted) K-Medoids instance for `n_clusters` groups.
+    pub fn new(n_clusters: usize) -> Self {
+        Self {
+            n_clusters,
+            medoid_ids: Vec::new(),
+            centers: Vec::new(),
+            clusters: Vec::new(),
+        }
+    }
+
+    /// Node ids of the medoids found by the last fit.
+    pub fn medoid_ids(&self) -> &[usize] {
+        &self.medoid_ids
+    }
+}
+
+impl Clusterer for KMedoids {
+    fn set_n_clusters(&mut self, n_clusters: usize) {
+        self.n_clusters = n_clusters;
+    }
+
+    /// Seeds the medoids with K-Means++, then alternates:
+    /// - assign every node to its nearest medoid
+    /// - move each medoid to the member with the smallest total distance to its cluster
+    ///
+    /// until the medoids stop changing or `MAX_ITER` is reached.
+    fn fit_indices(&mut self, wsn: &dyn NodeStore, indices: &[usize], mut rng: &mut dyn RngCore) {
+        let k = self.n_clusters.min(indices.len());
+
+        self.clusters = vec![None; wsn.node_count()];
+        self.centers.clear();
+
+        if k == 0 {
+            self.medoid_ids.clear();
+            return;
+        }
+
+        self.medoid_ids = kmeans_plus_plus(wsn, indices, &mut self.centers, k, &mut rng);
+
+        for _ in 0..MAX_ITER {
+            // Assignment step
+            let mut members: Vec<Vec<usize>> = vec![Vec::new(); k];
+            for &node_id in indices {
+                let cluster = nearest_center(wsn.position(node_id), &self.centers);
+                self.clusters[node_id] = Some(cluster);
+                members[cluster].push(node_id);
+            }
+
+            // Medoid update step
+            let mut changed = false;
+            for (cluster, cluster_members) in members.iter().enumerate() {
+                let best = cluster_members.iter().copied().min_by(|&a, &b| {
+                    let cost = |candidate: usize| -> f32 {
+                        cluster_members
+                            .iter()
+                            .map(|&other| (wsn.position(candidate) - wsn.position(other)).length())
+                            .sum()
+                    };
+                    cost(a).total_cmp(&cost(b))
+                });
+
+                if let Some(best) = best
+                    && best != self.medoid_ids[cluster]
+                {
+                    self.medoid_ids[cluster] = best;
+                    self.centers[cluster] = wsn.position(best);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn centers(&self) -> &[Vec2] {
+        &self.centers
+    }
+
+    fn clusters(&self) -> &[Option<usize>] {
+        &self.clusters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::tests::{assert_blobs_recovered, three_blobs};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn medoids_are_the_most_central_members() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let nodes = three_blobs(&mut rng);
+
+        let mut kmedoids = KMedoids::new(3);
+        kmedoids.fit_alive(&nodes, &mut rng);
+        assert_blobs_recovered(kmedoids.clusters());
+
+        for (cluster, &medoid_id) in kmedoids.medoid_ids().iter().enumerate() {
+            assert_eq!(kmedoids.centers()[cluster], nodes[medoid_id].position);
+            assert_eq!(kmedoids.clusters()[medoid_id], Some(cluster));
+
+            let members: Vec<usize> = (0..nodes.len())
+                .filter(|&id| kmedoids.clusters()[id] == Some(cluster))
+                .collect();
+            let total_distance = |candidate: usize| -> f32 {
+                members
+                    .iter()
+                    .map(|&id| (nodes[id].position - nodes[candidate].position).length())
+                    .sum()
+            };
+            assert!(members.iter().all(|&id| total_distance(medoid_id) <= total_distance(id) + 1e-3));
+        }
+    }
+}
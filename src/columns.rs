@@ -0,0 +1,562 @@
+use crate::node::Node;
+use crate::utils::reset_node_for_new_round;
+use glam::Vec2;
+use std::ops::Range;
+
+/// Per-node state accessors used by the protocols' round phases, so they can run on
+/// either the array-of-structs `Vec<Node>` or the struct-of-arrays `NodeColumns`.
+///
+/// Node ids are indices into the store.
+pub trait NodeStore {
+    /// Number of nodes (alive or dead)
+    fn node_count(&self) -> usize;
+
+    fn position(&self, id: usize) -> Vec2;
+
+    fn remaining_energy_j(&self, id: usize) -> f32;
+
+    fn distance_to_base_station_m(&self, id: usize) -> f32;
+
+    fn is_alive(&self, id: usize) -> bool;
+
+    fn is_cluster_head(&self, id: usize) -> bool;
+
+    fn is_eligible_for_ch(&self, id: usize) -> bool;
+
+    /// CH a member node belongs to (`None` for CHs and unclustered nodes)
+    fn cluster_head_id(&self, id: usize) -> Option<usize>;
+
+    fn last_cluster_head_round(&self, id: usize) -> Option<usize>;
+
+    fn radio_class(&self, id: usize) -> usize;
+
+    fn is_isolated(&self, id: usize) -> bool;
+
+    fn harvest_scale(&self, id: usize) -> f32;
+
+    /// Members of the CH `ch_id` (up to date after `finish_cluster_formation`)
+    fn cluster_member_ids(&self, ch_id: usize) -> &[usize];
+
+    /// Subtracts `energy_j` from the node's remaining energy.
+    fn consume_energy(&mut self, id: usize, energy_j: f32);
+
+    fn set_remaining_energy_j(&mut self, id: usize, energy_j: f32);
+
+    fn set_alive(&mut self, id: usize, is_alive: bool);
+
+    fn set_eligible_for_ch(&mut self, id: usize, is_eligible: bool);
+
+    fn set_isolated(&mut self, id: usize, is_isolated: bool);
+
+    /// Makes the node a CH for `round` and removes it from the rotation.
+    fn promote_to_cluster_head(&mut self, id: usize, round: usize);
+
+    /// Registers `member_id` as a member of the CH `ch_id`.
+    ///
+    /// Both stores list members in ascending id order when they join in that order.
+    fn join_cluster(&mut self, member_id: usize, ch_id: usize);
+
+    /// Makes `cluster_member_ids` reflect every `join_cluster` call of this round.
+    fn finish_cluster_formation(&mut self) {}
+
+    /// Passes the CH role and the cluster of `old_ch_id` to its member `new_ch_id`.
+    ///
+    /// The new CH leaves the member list, the old CH joins it at the end, and the new
+    /// CH inherits the old CH's `last_cluster_head_round`.
+    fn transfer_cluster_head(&mut self, old_ch_id: usize, new_ch_id: usize);
+
+    /// Clears every node's CH role, CH assignment, member list and isolation for a new round.
+    fn reset_round_state(&mut self);
+}
+
+impl NodeStore for Vec<Node> {
+    fn node_count(&self) -> usize {
+        self.len()
+    }
+
+    fn position(&self, id: usize) -> Vec2 {
+        self[id].position
+    }
+
+    fn remaining_energy_j(&self, id: usize) -> f32 {
+        self[id].remaining_energy_j
+    }
+
+    fn distance_to_base_station_m(&self, id: usize) -> f32 {
+        self[id].distance_to_base_station_m
+    }
+
+    fn is_alive(&self, id: usize) -> bool {
+        self[id].is_alive
+    }
+
+    fn is_cluster_head(&self, id: usize) -> bool {
+        self[id].is_cluster_head
+    }
+
+    fn is_eligible_for_ch(&self, id: usize) -> bool {
+        self[id].is_eligible_for_ch
+    }
+
+    fn cluster_head_id(&self, id: usize) -> Option<usize> {
+        self[id].cluster_head_id
+    }
+
+    fn last_cluster_head_round(&self, id: usize) -> Option<usize> {
+        self[id].last_cluster_head_round
+    }
+
+    fn radio_class(&self, id: usize) -> usize {
+        self[id].radio_class
+    }
+
+    fn is_isolated(&self, id: usize) -> bool {
+        self[id].is_isolated
+    }
+
+    fn harvest_scale(&self, id: usize) -> f32 {
+        self[id].harvest_scale
+    }
+
+    fn cluster_member_ids(&self, ch_id: usize) -> &[usize] {
+        &self[ch_id].cluster_member_ids
+    }
+
+    fn consume_energy(&mut self, id: usize, energy_j: f32) {
+        self[id].remaining_energy_j -= energy_j;
+    }
+
+    fn set_remaining_energy_j(&mut self, id: usize, energy_j: f32) {
+        self[id].remaining_energy_j = energy_j;
+    }
+
+    fn set_alive(&mut self, id: usize, is_alive: bool) {
+        self[id].is_alive = is_alive;
+    }
+
+    fn set_eligible_for_ch(&mut self, id: usize, is_eligible: bool) {
+        self[id].is_eligible_for_ch = is_eligible;
+    }
+
+    fn set_isolated(&mut self, id: usize, is_isolated: bool) {
+        self[id].is_isolated = is_isolated;
+    }
+
+    fn promote_to_cluster_head(&mut self, id: usize, round: usize) {
+        self[id].is_cluster_head = true;
+        self[id].is_eligible_for_ch = false;
+        self[id].last_cluster_head_round = Some(round);
+    }
+
+    fn join_cluster(&mut self, member_id: usize, ch_id: usize) {
+        self[member_id].cluster_head_id = Some(ch_id);
+        self[ch_id].cluster_member_ids.push(member_id);
+    }
+
+    fn transfer_cluster_head(&mut self, old_ch_id: usize, new_ch_id: usize) {
+        let mut member_ids = std::mem::take(&mut self[old_ch_id].cluster_member_ids);
+        member_ids.retain(|&id| id != new_ch_id);
+        member_ids.push(old_ch_id);
+
+        for &member_id in &member_ids {
+            self[member_id].cluster_head_id = Some(new_ch_id);
+        }
+
+        self[old_ch_id].is_cluster_head = false;
+        self[new_ch_id].is_cluster_head = true;
+        self[new_ch_id].is_eligible_for_ch = false;
+        self[new_ch_id].cluster_head_id = None;
+        self[new_ch_id].last_cluster_head_round = self[old_ch_id].last_cluster_head_round;
+        self[new_ch_id].cluster_member_ids = member_ids;
+    }
+
+    fn reset_round_state(&mut self) {
+        for node in self.iter_mut() {
+            reset_node_for_new_round(node);
+        }
+    }
+}
+
+/// Flat (CSR) cluster membership: the members of every CH stored back to back.
+#[derive(Debug, Clone, Default)]
+pub struct MembershipIndex {
+    /// `ranges[ch]` is the range of `ch`'s members in `member_ids` (empty for non-CHs)
+    ranges: Vec<Range<usize>>,
+
+    /// Member ids grouped by CH, ascending within each group until a handover
+    member_ids: Vec<usize>,
+}
+
+impl MembershipIndex {
+    /// Builds the index from every node's CH assignment (counting sort, O(N)).
+    pub fn from_cluster_head_ids(cluster_head_ids: &[Option<usize>]) -> Self {
+        let n = cluster_head_ids.len();
+        let mut offsets = vec![0; n + 1];
+
+        for &ch_id in cluster_head_ids.iter().flatten() {
+            offsets[ch_id + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let mut next = offsets.clone();
+        let mut member_ids = vec![0; offsets[n]];
+        for (member_id, &ch_id) in cluster_head_ids.iter().enumerate() {
+            if let Some(ch_id) = ch_id {
+                member_ids[next[ch_id]] = member_id;
+                next[ch_id] += 1;
+            }
+        }
+
+        let ranges = offsets.windows(2).map(|bounds| bounds[0]..bounds[1]).collect();
+        Self { ranges, member_ids }
+    }
+
+    /// Members of `ch_id` (empty for non-CHs).
+    pub fn members(&self, ch_id: usize) -> &[usize] {
+        self.ranges
+            .get(ch_id)
+            .map_or(&[], |range| &self.member_ids[range.clone()])
+    }
+
+    /// Moves the members of `old_ch_id` over to `new_ch_id`: `new_ch_id` leaves the
+    /// list and `old_ch_id` joins at its end.
+    ///
+    /// In place when `new_ch_id` was a member; otherwise the list grows and is moved
+    /// to the end of `member_ids`.
+    fn transfer(&mut self, old_ch_id: usize, new_ch_id: usize) {
+        let mut range = std::mem::take(&mut self.ranges[old_ch_id]);
+        let member_ids = &mut self.member_ids[range.clone()];
+
+        if let Some(i) = member_ids.iter().position(|&id| id == new_ch_id) {
+            member_ids[i..].rotate_left(1);
+            member_ids[member_ids.len() - 1] = old_ch_id;
+        } else {
+            let start = self.member_ids.len();
+            self.member_ids.extend_from_within(range);
+            self.member_ids.push(old_ch_id);
+            range = start..self.member_ids.len();
+        }
+        self.ranges[new_ch_id] = range;
+    }
+}
+
+/// Struct-of-arrays node storage: one contiguous column per node field plus a
+/// flat membership index instead of a member `Vec` per node.
+///
+/// Implements `NodeStore`. With `Simulator::with_columnar_storage` it owns the
+/// network and every round phase runs on it; `Node`s are only built on request.
+#[derive(Debug, Clone, Default)]
+pub struct NodeColumns {
+    pub positions: Vec<Vec2>,
+    pub remaining_energy_j: Vec<f32>,
+    pub distance_to_base_station_m: Vec<f32>,
+    pub is_alive: Vec<bool>,
+    pub is_cluster_head: Vec<bool>,
+    pub is_eligible_for_ch: Vec<bool>,
+    pub cluster_head_id: Vec<Option<usize>>,
+    pub last_cluster_head_round: Vec<Option<usize>>,
+    pub radio_class: Vec<usize>,
+    pub is_isolated: Vec<bool>,
+    pub harvest_scale: Vec<f32>,
+
+    /// Members of every CH, rebuilt from `cluster_head_id` by `finish_cluster_formation`
+    membership: MembershipIndex,
+}
+
+impl NodeColumns {
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether the network has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Appends `node` under the next id.
+    ///
+    /// Its member list is not copied: memberships follow `cluster_head_id` from the
+    /// next `finish_cluster_formation` on.
+    pub fn push(&mut self, node: &Node) {
+        self.positions.push(node.position);
+        self.remaining_energy_j.push(node.remaining_energy_j);
+        self.distance_to_base_station_m.push(node.distance_to_base_station_m);
+        self.is_alive.push(node.is_alive);
+        self.is_cluster_head.push(node.is_cluster_head);
+        self.is_eligible_for_ch.push(node.is_eligible_for_ch);
+        self.cluster_head_id.push(node.cluster_head_id);
+        self.last_cluster_head_round.push(node.last_cluster_head_round);
+        self.radio_class.push(node.radio_class);
+        self.is_isolated.push(node.is_isolated);
+        self.harvest_scale.push(node.harvest_scale);
+    }
+
+    /// Array-of-structs copy of node `id`, member list included.
+    pub fn node(&self, id: usize) -> Node {
+        Node {
+            id,
+            position: self.positions[id],
+            remaining_energy_j: self.remaining_energy_j[id],
+            is_alive: self.is_alive[id],
+            is_cluster_head: self.is_cluster_head[id],
+            is_eligible_for_ch: self.is_eligible_for_ch[id],
+            distance_to_base_station_m: self.distance_to_base_station_m[id],
+            cluster_head_id: self.cluster_head_id[id],
+            cluster_member_ids: self.membership.members(id).to_vec(),
+            last_cluster_head_round: self.last_cluster_head_round[id],
+            radio_class: self.radio_class[id],
+            is_isolated: self.is_isolated[id],
+            harvest_scale: self.harvest_scale[id],
+        }
+    }
+
+    /// Overwrites node `id` with `node` (member list ignored, as for `push`).
+    pub fn set_node(&mut self, id: usize, node: &Node) {
+        self.positions[id] = node.position;
+        self.remaining_energy_j[id] = node.remaining_energy_j;
+        self.distance_to_base_station_m[id] = node.distance_to_base_station_m;
+        self.is_alive[id] = node.is_alive;
+        self.is_cluster_head[id] = node.is_cluster_head;
+        self.is_eligible_for_ch[id] = node.is_eligible_for_ch;
+        self.cluster_head_id[id] = node.cluster_head_id;
+        self.last_cluster_head_round[id] = node.last_cluster_head_round;
+        self.radio_class[id] = node.radio_class;
+        self.is_isolated[id] = node.is_isolated;
+        self.harvest_scale[id] = node.harvest_scale;
+    }
+
+    /// Array-of-structs copy of the network.
+    pub fn to_nodes(&self) -> Vec<Node> {
+        (0..self.len()).map(|id| self.node(id)).collect()
+    }
+}
+
+impl From<&[Node]> for NodeColumns {
+    fn from(nodes: &[Node]) -> Self {
+        let mut columns = Self::default();
+        for node in nodes {
+            columns.push(node);
+        }
+        columns.membership = MembershipIndex::from_cluster_head_ids(&columns.cluster_head_id);
+        columns
+    }
+}
+
+impl NodeStore for NodeColumns {
+    fn node_count(&self) -> usize {
+        self.len()
+    }
+
+    fn position(&self, id: usize) -> Vec2 {
+        self.positions[id]
+    }
+
+    fn remaining_energy_j(&self, id: usize) -> f32 {
+        self.remaining_energy_j[id]
+    }
+
+    fn distance_to_base_station_m(&self, id: usize) -> f32 {
+        self.distance_to_base_station_m[id]
+    }
+
+    fn is_alive(&self, id: usize) -> bool {
+        self.is_alive[id]
+    }
+
+    fn is_cluster_head(&self, id: usize) -> bool {
+        self.is_cluster_head[id]
+    }
+
+    fn is_eligible_for_ch(&self, id: usize) -> bool {
+        self.is_eligible_for_ch[id]
+    }
+
+    fn cluster_head_id(&self, id: usize) -> Option<usize> {
+        self.cluster_head_id[id]
+    }
+
+    fn last_cluster_head_round(&self, id: usize) -> Option<usize> {
+        self.last_cluster_head_round[id]
+    }
+
+    fn radio_class(&self, id: usize) -> usize {
+        self.radio_class[id]
+    }
+
+    fn is_isolated(&self, id: usize) -> bool {
+        self.is_isolated[id]
+    }
+
+    fn harvest_scale(&self, id: usize) -> f32 {
+        self.harvest_scale[id]
+    }
+
+    fn cluster_member_ids(&self, ch_id: usize) -> &[usize] {
+        self.membership.members(ch_id)
+    }
+
+    fn consume_energy(&mut self, id: usize, energy_j: f32) {
+        self.remaining_energy_j[id] -= energy_j;
+    }
+
+    fn set_remaining_energy_j(&mut self, id: usize, energy_j: f32) {
+        self.remaining_energy_j[id] = energy_j;
+    }
+
+    fn set_alive(&mut self, id: usize, is_alive: bool) {
+        self.is_alive[id] = is_alive;
+    }
+
+    fn set_eligible_for_ch(&mut self, id: usize, is_eligible: bool) {
+        self.is_eligible_for_ch[id] = is_eligible;
+    }
+
+    fn set_isolated(&mut self, id: usize, is_isolated: bool) {
+        self.is_isolated[id] = is_isolated;
+    }
+
+    fn promote_to_cluster_head(&mut self, id: usize, round: usize) {
+        self.is_cluster_head[id] = true;
+        self.is_eligible_for_ch[id] = false;
+        self.last_cluster_head_round[id] = Some(round);
+    }
+
+    fn join_cluster(&mut self, member_id: usize, ch_id: usize) {
+        self.cluster_head_id[member_id] = Some(ch_id);
+    }
+
+    fn finish_cluster_formation(&mut self) {
+        self.membership = MembershipIndex::from_cluster_head_ids(&self.cluster_head_id);
+    }
+
+    fn transfer_cluster_head(&mut self, old_ch_id: usize, new_ch_id: usize) {
+        self.membership.transfer(old_ch_id, new_ch_id);
+
+        for &member_id in self.membership.members(new_ch_id) {
+            self.cluster_head_id[member_id] = Some(new_ch_id);
+        }
+
+        self.is_cluster_head[old_ch_id] = false;
+        self.is_cluster_head[new_ch_id] = true;
+        self.is_eligible_for_ch[new_ch_id] = false;
+        self.cluster_head_id[new_ch_id] = None;
+        self.last_cluster_head_round[new_ch_id] = self.last_cluster_head_round[old_ch_id];
+    }
+
+    fn reset_round_state(&mut self) {
+        self.is_cluster_head.fill(false);
+        self.cluster_head_id.fill(None);
+        self.is_isolated.fill(false);
+        self.membership = MembershipIndex::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handover::HandoverConfig;
+    use crate::leach::Leach;
+    use crate::simulator::{Protocol, Simulator};
+    use crate::zcr::Zcr;
+
+    /// CH 0 with members 1–3, CH 4 with members 5 and 6, node 7 unclustered.
+    fn clustered_nodes() -> Vec<Node> {
+        let mut nodes: Vec<Node> = (0..8).map(|id| Node::new(id, Vec2::new(id as f32, 0.0))).collect();
+        nodes.promote_to_cluster_head(0, 1);
+        nodes.promote_to_cluster_head(4, 2);
+        for (member_id, ch_id) in [(1, 0), (2, 0), (3, 0), (5, 4), (6, 4)] {
+            nodes.join_cluster(member_id, ch_id);
+        }
+        nodes.finish_cluster_formation();
+        nodes
+    }
+
+    /// Asserts that both stores hold the same clusters.
+    fn assert_same_clusters(rows: &Vec<Node>, columns: &NodeColumns) {
+        for id in 0..rows.node_count() {
+            assert_eq!(rows.is_cluster_head(id), columns.is_cluster_head(id), "node {id}");
+            assert_eq!(rows.cluster_head_id(id), columns.cluster_head_id(id), "node {id}");
+            assert_eq!(rows.cluster_member_ids(id), columns.cluster_member_ids(id), "node {id}");
+            assert_eq!(rows.last_cluster_head_round(id), columns.last_cluster_head_round(id), "node {id}");
+        }
+    }
+
+    #[test]
+    fn transfer_moves_the_cluster_to_the_new_head() {
+        let mut rows = clustered_nodes();
+        let mut columns = NodeColumns::from(&rows[..]);
+        assert_same_clusters(&rows, &columns);
+
+        rows.transfer_cluster_head(0, 2);
+        columns.transfer_cluster_head(0, 2);
+        assert_eq!(columns.cluster_member_ids(2), [1, 3, 0]);
+        assert_eq!(columns.cluster_member_ids(0), [] as [usize; 0]);
+        assert_eq!(columns.last_cluster_head_round(2), Some(1));
+        assert_same_clusters(&rows, &columns);
+
+        // A second handover within the same round
+        rows.transfer_cluster_head(2, 3);
+        columns.transfer_cluster_head(2, 3);
+        assert_eq!(columns.cluster_member_ids(3), [1, 0, 2]);
+        assert_same_clusters(&rows, &columns);
+    }
+
+    #[test]
+    fn transfer_to_a_non_member_appends_the_old_head() {
+        let mut rows = clustered_nodes();
+        let mut columns = NodeColumns::from(&rows[..]);
+
+        rows.transfer_cluster_head(4, 7);
+        columns.transfer_cluster_head(4, 7);
+        assert_eq!(columns.cluster_member_ids(7), [5, 6, 4]);
+        assert_same_clusters(&rows, &columns);
+    }
+
+    /// Per-round state of every node and the round metrics of `rounds` rounds of
+    /// `protocol`, with or without columnar storage, and the number of handovers.
+    fn run(protocol: &mut impl Protocol, columnar: bool, rounds: usize) -> (Vec<String>, usize) {
+        let mut simulator = Simulator::with_seed(300.0, 300.0, 120, 11).with_cluster_quality();
+        if columnar {
+            simulator = simulator.with_columnar_storage();
+        }
+
+        let mut handovers = 0;
+        let states = (0..rounds)
+            .map(|round| {
+                if round == 40 {
+                    simulator.move_node(3, Vec2::new(10.0, 10.0));
+                    simulator.add_node(Vec2::new(150.0, 150.0));
+                }
+                simulator.update(protocol);
+                handovers += simulator.metrics.cluster_head_handovers;
+                format!("{:?} {:?}", simulator.nodes(), simulator.metrics)
+            })
+            .collect();
+        (states, handovers)
+    }
+
+    #[test]
+    fn columnar_runs_match_row_runs() {
+        let rounds = 150;
+        assert_eq!(run(&mut Leach::new(0.1), true, rounds), run(&mut Leach::new(0.1), false, rounds));
+
+        // CHs hand over as soon as they spent a little energy
+        let handover = HandoverConfig {
+            energy_threshold_j: 1.98,
+            ..HandoverConfig::default()
+        };
+
+        let leach_handover = || Leach::new(0.1).with_handover(handover);
+        let (states, handovers) = run(&mut leach_handover(), true, rounds);
+        assert!(handovers > 0);
+        assert_eq!((states, handovers), run(&mut leach_handover(), false, rounds));
+
+        let zcr_handover = || Zcr::new(0.1).with_handover(handover);
+        let (states, handovers) = run(&mut zcr_handover(), true, rounds);
+        assert!(handovers > 0);
+        assert_eq!((states, handovers), run(&mut zcr_handover(), false, rounds));
+    }
+}
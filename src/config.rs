@@ -66,6 +66,85 @@ pub const ENERGY_AGGREGATION_J: f32 = 5e-9;
 /// Size of a data packet (bits).
 pub const DATA_PACKET_SIZE_BITS: f32 = 4000.0;
 
+/// Size of a control packet — advertisements, joins, handovers (bits).
+pub const CONTROL_PACKET_SIZE_BITS: f32 = 200.0;
+
+// =============================================================================
+// Power States (Idle Listening, Sleep, Sensing, MCU)
+// =============================================================================
+/// Duration of one round (seconds).
+pub const ROUND_DURATION_S: f32 = 20.0;
+
+/// Radio bit rate, converts packet sizes into airtime (bits/s).
+pub const RADIO_DATA_RATE_BPS: f32 = 250_000.0;
+
+/// Draw while transmitting, on top of the per-bit radio model (W).
+pub const POWER_TX_W: f32 = 0.0;
+
+/// Draw while receiving, on top of the per-bit radio model (W).
+pub const POWER_RX_W: f32 = 0.0;
+
+/// Draw of a radio listening to an idle channel (W).
+pub const POWER_IDLE_W: f32 = 0.0564;
+
+/// Draw of a node with radio and MCU asleep (W).
+pub const POWER_SLEEP_W: f32 = 3e-6;
+
+/// Draw of the sensor while sampling (W).
+pub const POWER_SENSE_W: f32 = 0.003;
+
+/// Draw of the MCU while awake (W).
+pub const POWER_MCU_W: f32 = 0.0018;
+
+/// Time needed to take one sensor reading (seconds).
+pub const SENSING_TIME_S: f32 = 0.01;
+
+// =============================================================================
+// Battery Models
+// =============================================================================
+/// Peukert exponent of the rate-capacity battery model (1 → ideal battery).
+pub const BATTERY_PEUKERT_EXPONENT: f32 = 1.2;
+
+/// Discharge power at which a Peukert battery delivers its nominal capacity (W).
+pub const BATTERY_RATED_POWER_W: f32 = 1e-4;
+
+/// Fraction of the charge held in the available well of the Kinetic Battery Model.
+pub const KIBAM_AVAILABLE_FRACTION: f32 = 0.625;
+
+/// Rate constant of the flow between the KiBaM wells (1/s).
+pub const KIBAM_RATE_CONSTANT_PER_S: f32 = 1e-4;
+
+/// Open-circuit voltage of a full battery (V).
+pub const BATTERY_FULL_VOLTAGE_V: f32 = 3.0;
+
+/// Open-circuit voltage of an empty battery (V).
+pub const BATTERY_EMPTY_VOLTAGE_V: f32 = 2.0;
+
+/// Voltage below which a node browns out (V); equal to the empty voltage → no
+/// early cutoff.
+pub const BATTERY_CUTOFF_VOLTAGE_V: f32 = 2.0;
+
+// =============================================================================
+// Energy Harvesting
+// =============================================================================
+/// Harvesting power of the constant profile (W).
+pub const HARVEST_CONSTANT_POWER_W: f32 = 1.0e-4;
+
+/// Harvesting power at solar noon of the day/night and cloudy profiles (W).
+pub const HARVEST_PEAK_POWER_W: f32 = 2.0e-4;
+
+/// Length of one day/night cycle (seconds).
+pub const HARVEST_DAY_LENGTH_S: f32 = 86_400.0;
+
+/// Probability that a round of the cloudy profile is overcast.
+pub const HARVEST_CLOUD_PROBABILITY: f32 = 0.3;
+
+/// Largest fraction of the sunlight that gets through an overcast sky.
+pub const HARVEST_CLOUD_TRANSMISSION: f32 = 0.3;
+
+/// Stored energy a dead node needs before it rejoins the network (Joules).
+pub const HARVEST_REVIVAL_THRESHOLD_J: f32 = 0.2;
+
 // =============================================================================
 // Radio Propagation & Threshold
 // =============================================================================
@@ -73,6 +152,83 @@ pub const DATA_PACKET_SIZE_BITS: f32 = 4000.0;
 /// Computed as sqrt( E_FREE_SPACE_AMP / E_MULTIPATH_AMP ).
 pub const FS_MULTIPATH_THRESHOLD_DISTANCE_M: f32 = 87.7;
 
+// =============================================================================
+// Round Structure
+// =============================================================================
+/// Steady-state TDMA frames per round: every member sends one data packet per
+/// frame, while cluster setup happens once per round.
+pub const STEADY_STATE_FRAMES_PER_ROUND: usize = 1;
+
+// =============================================================================
+// Link Layer (Packet Loss)
+// =============================================================================
+/// Path-loss exponent of the log-normal shadowing link model.
+pub const LINK_PATH_LOSS_EXPONENT: f32 = 3.0;
+
+/// Standard deviation of the log-normal shadowing term (dB).
+pub const LINK_SHADOWING_STD_DEV_DB: f32 = 4.0;
+
+/// Hop length at which half of the packets are received under log-normal
+/// shadowing (meters).
+pub const LINK_HALF_RECEPTION_DISTANCE_M: f32 = 150.0;
+
+// =============================================================================
+// MAC Layer (CSMA Setup, TDMA Data Phase)
+// =============================================================================
+/// Number of backoff slots a cluster head chooses from before sending its
+/// advertisement under CSMA.
+pub const MAC_CONTENTION_WINDOW_SLOTS: usize = 16;
+
+/// Radius around a cluster head within which a transmitter of another cluster
+/// corrupts the packet it receives in the same TDMA slot (meters).
+pub const MAC_INTERFERENCE_RANGE_M: f32 = 50.0;
+
+// =============================================================================
+// Control Plane (Cluster Setup Messages)
+// =============================================================================
+/// Size of a cluster-head advertisement (bits).
+pub const CONTROL_ADVERTISEMENT_SIZE_BITS: f32 = CONTROL_PACKET_SIZE_BITS;
+
+/// Size of a join request sent by a member to its cluster head (bits).
+pub const CONTROL_JOIN_REQUEST_SIZE_BITS: f32 = CONTROL_PACKET_SIZE_BITS;
+
+/// Size of a TDMA schedule broadcast by a cluster head (bits).
+pub const CONTROL_SCHEDULE_SIZE_BITS: f32 = CONTROL_PACKET_SIZE_BITS;
+
+/// Size of a node's position / energy report to the base station (bits).
+pub const CONTROL_STATUS_REPORT_SIZE_BITS: f32 = CONTROL_PACKET_SIZE_BITS;
+
+/// Size of the role / cluster assignment the base station sends to each node (bits).
+pub const CONTROL_ASSIGNMENT_SIZE_BITS: f32 = CONTROL_PACKET_SIZE_BITS;
+
+// =============================================================================
+// Sensor Field & Data Aggregation
+// =============================================================================
+/// Value of the synthetic environmental field away from any hotspot (°C).
+pub const FIELD_BACKGROUND_VALUE: f32 = 20.0;
+
+/// Number of moving hotspots in the synthetic field.
+pub const FIELD_HOTSPOT_COUNT: usize = 3;
+
+/// Peak value a hotspot adds on top of the background (°C).
+pub const FIELD_HOTSPOT_AMPLITUDE: f32 = 15.0;
+
+/// Standard deviation of a hotspot's Gaussian footprint (meters).
+pub const FIELD_HOTSPOT_RADIUS_M: f32 = 50.0;
+
+/// Speed at which hotspots drift across the area (m/s).
+pub const FIELD_HOTSPOT_SPEED_MPS: f32 = 0.1;
+
+/// Resolution of a compressed reading (°C).
+pub const FIELD_QUANTIZATION_STEP: f32 = 0.5;
+
+/// Size of a compressed reading relative to a raw data packet.
+pub const FIELD_COMPRESSION_RATIO: f32 = 0.25;
+
+/// Points per side of the grid on which the sink's reconstruction is compared
+/// with the true field.
+pub const FIELD_RECONSTRUCTION_GRID_POINTS: usize = 25;
+
 // =============================================================================
 // Base Station (Sink)
 // =============================================================================
@@ -80,6 +236,52 @@ pub const FS_MULTIPATH_THRESHOLD_DISTANCE_M: f32 = 87.7;
 pub const BASE_STATION_POSITION: Vec2 =
     Vec2::new(DEPLOYMENT_AREA_WIDTH_M / 2.0, DEPLOYMENT_AREA_HEIGHT_M / 2.0);
 
+// =============================================================================
+// Clustering Algorithms
+// =============================================================================
+/// DBSCAN neighbourhood radius (meters).
+pub const DBSCAN_EPS_M: f32 = 60.0;
+
+/// Minimum number of nodes (including itself) within `DBSCAN_EPS_M` for a node
+/// to be a DBSCAN core point.
+pub const DBSCAN_MIN_POINTS: usize = 4;
+
+/// Cluster capacity of balanced K-Means as a multiple of the perfectly balanced
+/// size `ceil(n / k)` (1.0 → strictly equal sizes).
+pub const BALANCED_KMEANS_CAPACITY_SLACK: f32 = 1.1;
+
+/// Number of uniform reference data sets drawn per k by the gap statistic.
+pub const GAP_STATISTIC_REFERENCE_SETS: usize = 10;
+
+// =============================================================================
+// Cluster-Head Handover
+// =============================================================================
+/// Residual energy below which a cluster head hands its role over to a member
+/// in the middle of a round (Joules).
+pub const HANDOVER_ENERGY_THRESHOLD_J: f32 = 0.01;
+
+// =============================================================================
+// ZCR Protocol Parameters
+// =============================================================================
+/// Maximum number of far-zone cluster heads a single near-zone cluster head
+/// will accept as relay traffic within one round.
+pub const ZCR_MAX_RELAYS_PER_CLUSTER_HEAD: usize = 2;
+
+/// Radius within which alive nodes count as neighbours for the
+/// node-degree term of the cluster-head score (meters).
+pub const CH_SCORING_NEIGHBOUR_RADIUS_M: f32 = FS_MULTIPATH_THRESHOLD_DISTANCE_M;
+
+/// Number of rounds ZCR keeps its K-Means clusters fixed before re-clustering
+/// (1 → re-cluster every round).
+pub const ZCR_RECLUSTER_INTERVAL_ROUNDS: usize = 1;
+
+/// Whether ZCR re-clusters early when a cluster head of the previous round died.
+pub const ZCR_RECLUSTER_ON_CLUSTER_HEAD_DEATH: bool = true;
+
+/// ZCR re-clusters early once any cluster's total residual energy falls below
+/// this fraction of its energy at the time the clusters were formed.
+pub const ZCR_RECLUSTER_ENERGY_DROP_FRACTION: f32 = 0.5;
+
 // =============================================================================
 // Simulation Control
 // =============================================================================
@@ -0,0 +1,312 @@
+use crate::columns::NodeStore;
+use crate::config::{
+    CONTROL_ADVERTISEMENT_SIZE_BITS,
+    CONTROL_ASSIGNMENT_SIZE_BITS,
+    CONTROL_JOIN_REQUEST_SIZE_BITS,
+    CONTROL_SCHEDULE_SIZE_BITS,
+    CONTROL_STATUS_REPORT_SIZE_BITS,
+};
+use crate::distance::{node_distance, DistanceCache};
+use crate::link::LinkLayer;
+use crate::metrics::RoundMetrics;
+use crate::spatial::SpatialGrid;
+use crate::utils::{calculate_receive_energy, calculate_transmit_energy};
+
+/// Sizes of the cluster-setup control messages the protocols are charged for.
+///
+/// - LEACH (distributed): CH advertisements, member join requests, TDMA schedules
+/// - ZCR (centralized): position / energy reports to the BS, role / cluster
+///   assignments from the BS, TDMA schedules
+/// - Advertisements and schedules are left to the MAC layer when it is enabled,
+///   so they are never charged twice
+/// - The base station is mains-powered: its receptions and broadcasts are free
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ControlPlane {
+    /// Size of a CH advertisement (bits)
+    pub advertisement_bits: f32,
+
+    /// Size of a member's join request to its CH (bits)
+    pub join_request_bits: f32,
+
+    /// Size of a CH's TDMA schedule broadcast (bits)
+    pub schedule_bits: f32,
+
+    /// Size of a node's status report to the BS (bits)
+    pub status_report_bits: f32,
+
+    /// Size of the assignment the BS sends to each node (bits)
+    pub assignment_bits: f32,
+}
+
+impl Default for ControlPlane {
+    fn default() -> Self {
+        Self {
+            advertisement_bits: CONTROL_ADVERTISEMENT_SIZE_BITS,
+            join_request_bits: CONTROL_JOIN_REQUEST_SIZE_BITS,
+            schedule_bits: CONTROL_SCHEDULE_SIZE_BITS,
+            status_report_bits: CONTROL_STATUS_REPORT_SIZE_BITS,
+            assignment_bits: CONTROL_ASSIGNMENT_SIZE_BITS,
+        }
+    }
+}
+
+impl ControlPlane {
+    /// Every CH broadcasts an advertisement over its radio range (capped at
+    /// `broadcast_radius_m`); every other alive node within reach receives it.
+    pub fn charge_advertisements<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &mut S,
+        cluster_head_ids: &[usize],
+        link: &LinkLayer,
+        broadcast_radius_m: f32,
+        metrics: &mut RoundMetrics,
+    ) {
+        let radii: Vec<f32> = cluster_head_ids
+            .iter()
+            .map(|&ch_id| link.radio_range().max_range_m(nodes.radio_class(ch_id)).min(broadcast_radius_m))
+            .collect();
+        let max_radius = radii.iter().copied().fold(0.0, f32::max);
+
+        for (&ch_id, &radius) in cluster_head_ids.iter().zip(&radii) {
+            let energy = calculate_transmit_energy(self.advertisement_bits, radius);
+            nodes.consume_energy(ch_id, energy);
+            metrics.control_energy_j += energy;
+            metrics.control_packets += 1;
+        }
+
+        // CHs indexed by their position in `cluster_head_ids`
+        let cluster_head_grid = SpatialGrid::new(
+            cluster_head_ids
+                .iter()
+                .enumerate()
+                .map(|(i, &ch_id)| (i, nodes.position(ch_id))),
+        );
+        let receive_energy = calculate_receive_energy(self.advertisement_bits);
+
+        for node_id in 0..nodes.node_count() {
+            if !nodes.is_alive(node_id) || nodes.is_cluster_head(node_id) {
+                continue;
+            }
+
+            let mut received = 0usize;
+            cluster_head_grid.for_each_within_radius(nodes.position(node_id), max_radius, |i, distance| {
+                if distance <= radii[i] {
+                    received += 1;
+                }
+            });
+
+            let energy = receive_energy * received as f32;
+            nodes.consume_energy(node_id, energy);
+            metrics.control_energy_j += energy;
+        }
+    }
+
+    /// Every member sends a join request to its CH, which receives them all.
+    ///
+    /// Call after cluster formation.
+    pub fn charge_join_requests<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &mut S,
+        cluster_head_ids: &[usize],
+        distances: Option<&DistanceCache>,
+        metrics: &mut RoundMetrics,
+    ) {
+        let receive_energy = calculate_receive_energy(self.join_request_bits);
+
+        for &ch_id in cluster_head_ids {
+            let requests: Vec<(usize, f32)> = nodes
+                .cluster_member_ids(ch_id)
+                .iter()
+                .map(|&member_id| (member_id, node_distance(nodes, distances, member_id, ch_id)))
+                .collect();
+
+            for (member_id, distance) in requests {
+                let transmit_energy = calculate_transmit_energy(self.join_request_bits, distance);
+                nodes.consume_energy(member_id, transmit_energy);
+                nodes.consume_energy(ch_id, receive_energy);
+                metrics.control_energy_j += transmit_energy + receive_energy;
+                metrics.control_packets += 1;
+            }
+        }
+    }
+
+    /// Every CH with members broadcasts its TDMA schedule to them.
+    ///
+    /// Call after cluster formation.
+    pub fn charge_schedules<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &mut S,
+        cluster_head_ids: &[usize],
+        distances: Option<&DistanceCache>,
+        metrics: &mut RoundMetrics,
+    ) {
+        for &ch_id in cluster_head_ids {
+            if nodes.cluster_member_ids(ch_id).is_empty() {
+                continue;
+            }
+            metrics.control_energy_j += broadcast_to_members(nodes, distances, ch_id, self.schedule_bits);
+            metrics.control_packets += 1;
+        }
+    }
+
+    /// Every alive node reports its position and residual energy straight to the BS.
+    ///
+    /// Reports are assumed to arrive (the centralized protocol cannot work without
+    /// them), so nodes beyond their radio range are charged as if in range.
+    pub fn charge_status_reports<S: NodeStore + ?Sized>(&self, nodes: &mut S, metrics: &mut RoundMetrics) {
+        for node_id in 0..nodes.node_count() {
+            if !nodes.is_alive(node_id) {
+                continue;
+            }
+            let energy = calculate_transmit_energy(self.status_report_bits, nodes.distance_to_base_station_m(node_id));
+            nodes.consume_energy(node_id, energy);
+            metrics.control_energy_j += energy;
+            metrics.control_packets += 1;
+        }
+    }
+
+    /// The BS tells every alive node its role and cluster; nodes pay RX.
+    pub fn charge_assignments<S: NodeStore + ?Sized>(&self, nodes: &mut S, metrics: &mut RoundMetrics) {
+        let receive_energy = calculate_receive_energy(self.assignment_bits);
+
+        for node_id in 0..nodes.node_count() {
+            if !nodes.is_alive(node_id) {
+                continue;
+            }
+            nodes.consume_energy(node_id, receive_energy);
+            metrics.control_energy_j += receive_energy;
+            metrics.control_packets += 1;
+        }
+    }
+}
+
+/// CH `ch_id` broadcasts `bits` to its farthest member; every member pays RX.
+///
+/// Returns the energy spent by the CH and its members.
+pub(crate) fn broadcast_to_members<S: NodeStore + ?Sized>(
+    nodes: &mut S,
+    distances: Option<&DistanceCache>,
+    ch_id: usize,
+    bits: f32,
+) -> f32 {
+    let receive_energy = calculate_receive_energy(bits);
+    let mut broadcast_radius: f32 = 0.0;
+    let mut energy = 0.0;
+
+    for member_id in nodes.cluster_member_ids(ch_id).to_vec() {
+        broadcast_radius = broadcast_radius.max(node_distance(nodes, distances, member_id, ch_id));
+        nodes.consume_energy(member_id, receive_energy);
+        energy += receive_energy;
+    }
+
+    let transmit_energy = calculate_transmit_energy(bits, broadcast_radius);
+    nodes.consume_energy(ch_id, transmit_energy);
+    energy + transmit_energy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+    use glam::Vec2;
+
+    /// Starting energy of the test nodes, small enough for f32 to resolve the
+    /// microjoules a control message costs (Joules)
+    const START_ENERGY_J: f32 = 1.0e-3;
+
+    /// CH 0 with members 1 (10 m away) and 2 (20 m away), CH 3 without members,
+    /// node 4 dead.
+    fn clustered_nodes() -> Vec<Node> {
+        let mut nodes: Vec<Node> = [(0.0, 0.0), (10.0, 0.0), (0.0, 20.0), (100.0, 0.0), (5.0, 5.0)]
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| Node::new(id, Vec2::new(x, y)))
+            .collect();
+        nodes.promote_to_cluster_head(0, 1);
+        nodes.promote_to_cluster_head(3, 1);
+        nodes.join_cluster(1, 0);
+        nodes.join_cluster(2, 0);
+        nodes.finish_cluster_formation();
+        nodes[4].is_alive = false;
+        for node in &mut nodes {
+            node.remaining_energy_j = START_ENERGY_J;
+        }
+        nodes
+    }
+
+    /// Energy each node spent so far (Joules).
+    fn spent(nodes: &[Node]) -> Vec<f32> {
+        nodes.iter().map(|node| START_ENERGY_J - node.remaining_energy_j).collect()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() <= 1e-9 + expected.abs() * 1e-3, "{actual} vs {expected}");
+    }
+
+    #[test]
+    fn join_requests_cost_members_tx_and_cluster_heads_rx() {
+        let control = ControlPlane::default();
+        let mut nodes = clustered_nodes();
+        let mut metrics = RoundMetrics::default();
+        control.charge_join_requests(&mut nodes, &[0, 3], None, &mut metrics);
+
+        let spent = spent(&nodes);
+        assert_eq!(metrics.control_packets, 2);
+        assert_close(spent[0], 2.0 * calculate_receive_energy(control.join_request_bits));
+        assert_close(spent[1], calculate_transmit_energy(control.join_request_bits, 10.0));
+        assert_close(spent[2], calculate_transmit_energy(control.join_request_bits, 20.0));
+        assert_eq!(spent[3], 0.0);
+        assert_close(metrics.control_energy_j, spent.iter().sum());
+    }
+
+    #[test]
+    fn schedules_reach_the_farthest_member() {
+        let control = ControlPlane::default();
+        let mut nodes = clustered_nodes();
+        let mut metrics = RoundMetrics::default();
+        control.charge_schedules(&mut nodes, &[0, 3], None, &mut metrics);
+
+        // CH 3 has nobody to schedule
+        let spent = spent(&nodes);
+        assert_eq!(metrics.control_packets, 1);
+        assert_close(spent[0], calculate_transmit_energy(control.schedule_bits, 20.0));
+        assert_close(spent[1], calculate_receive_energy(control.schedule_bits));
+        assert_eq!(spent[3], 0.0);
+        assert_close(metrics.control_energy_j, spent.iter().sum());
+    }
+
+    #[test]
+    fn advertisements_reach_nodes_within_the_broadcast_radius() {
+        let control = ControlPlane::default();
+        let mut nodes = clustered_nodes();
+        let mut metrics = RoundMetrics::default();
+        control.charge_advertisements(&mut nodes, &[0, 3], &LinkLayer::default(), 15.0, &mut metrics);
+
+        let spent = spent(&nodes);
+        assert_eq!(metrics.control_packets, 2);
+        assert_close(spent[0], calculate_transmit_energy(control.advertisement_bits, 15.0));
+        assert_close(spent[1], calculate_receive_energy(control.advertisement_bits));
+        assert_eq!(spent[2], 0.0);
+        assert_eq!(spent[4], 0.0);
+        assert_close(metrics.control_energy_j, spent.iter().sum());
+    }
+
+    #[test]
+    fn base_station_exchange_involves_alive_nodes_only() {
+        let control = ControlPlane::default();
+        let mut nodes = clustered_nodes();
+        let mut metrics = RoundMetrics::default();
+        control.charge_status_reports(&mut nodes, &mut metrics);
+        control.charge_assignments(&mut nodes, &mut metrics);
+
+        let spent = spent(&nodes);
+        assert_eq!(metrics.control_packets, 8);
+        for id in 0..4 {
+            let expected = calculate_transmit_energy(control.status_report_bits, nodes[id].distance_to_base_station_m)
+                + calculate_receive_energy(control.assignment_bits);
+            assert_close(spent[id], expected);
+        }
+        assert_eq!(spent[4], 0.0);
+        assert_close(metrics.control_energy_j, spent.iter().sum());
+    }
+}
@@ -0,0 +1,277 @@
+use crate::columns::NodeStore;
+use crate::spatial::SpatialGrid;
+use glam::Vec2;
+
+/// What the distance cache precomputes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceCacheMode {
+    /// Every pairwise distance (condensed upper triangle, N·(N-1)/2 floats)
+    Matrix,
+
+    /// For every node, the nodes within `range_m` and their distances
+    NeighbourTables { range_m: f32 },
+}
+
+/// Cached node-to-node distances for a static network.
+///
+/// The cache remembers the positions it was built from; `refresh` rebuilds it
+/// whenever nodes were added, removed or moved since.
+#[derive(Debug, Clone)]
+pub struct DistanceCache {
+    /// What is precomputed
+    mode: DistanceCacheMode,
+
+    /// Node positions at the last rebuild
+    positions: Vec<Vec2>,
+
+    /// Condensed pairwise distances (`Matrix` mode only)
+    matrix: Vec<f32>,
+
+    /// `neighbour_offsets[i]..neighbour_offsets[i + 1]` is node i's range in `neighbours`
+    /// (`NeighbourTables` mode only)
+    neighbour_offsets: Vec<usize>,
+
+    /// `(neighbour id, distance)` per node, sorted by id
+    neighbours: Vec<(usize, f32)>,
+}
+
+impl DistanceCache {
+    /// Builds the cache for `nodes`.
+    pub fn new<S: NodeStore + ?Sized>(mode: DistanceCacheMode, nodes: &S) -> Self {
+        let mut cache = Self {
+            mode,
+            positions: Vec::new(),
+            matrix: Vec::new(),
+            neighbour_offsets: Vec::new(),
+            neighbours: Vec::new(),
+        };
+        cache.rebuild(nodes);
+        cache
+    }
+
+    /// Which distances are precomputed.
+    pub fn mode(&self) -> DistanceCacheMode {
+        self.mode
+    }
+
+    /// Whether the cache no longer matches the node count or positions of `nodes`.
+    pub fn is_stale<S: NodeStore + ?Sized>(&self, nodes: &S) -> bool {
+        self.positions.len() != nodes.node_count()
+            || self
+                .positions
+                .iter()
+                .enumerate()
+                .any(|(id, &position)| nodes.position(id) != position)
+    }
+
+    /// Forces a rebuild on the next `refresh`.
+    pub fn invalidate(&mut self) {
+        self.positions.clear();
+        self.matrix.clear();
+        self.neighbour_offsets.clear();
+        self.neighbours.clear();
+    }
+
+    /// Rebuilds the cache if it is stale; returns whether it was rebuilt.
+    pub fn refresh<S: NodeStore + ?Sized>(&mut self, nodes: &S) -> bool {
+        if !self.is_stale(nodes) {
+            return false;
+        }
+        self.rebuild(nodes);
+        true
+    }
+
+    /// Distance between nodes `a` and `b` (meters).
+    ///
+    /// Pairs outside the neighbour range are computed from the cached positions.
+    pub fn distance(&self, a: usize, b: usize) -> f32 {
+        if a == b {
+            return 0.0;
+        }
+
+        match self.mode {
+            DistanceCacheMode::Matrix => self.matrix[self.matrix_index(a, b)],
+            DistanceCacheMode::NeighbourTables { .. } => self
+                .neighbours(a)
+                .binary_search_by_key(&b, |&(id, _)| id)
+                .map(|i| self.neighbours(a)[i].1)
+                .unwrap_or_else(|_| (self.positions[a] - self.positions[b]).length()),
+        }
+    }
+
+    /// Nodes within the neighbour range of `id` as `(id, distance)`, sorted by id
+    /// (empty in `Matrix` mode).
+    pub fn neighbours(&self, id: usize) -> &[(usize, f32)] {
+        match (self.neighbour_offsets.get(id), self.neighbour_offsets.get(id + 1)) {
+            (Some(&start), Some(&end)) => &self.neighbours[start..end],
+            _ => &[],
+        }
+    }
+
+    /// Alive neighbours within `radius_m` of every alive node (0 for dead nodes),
+    /// or `None` if the neighbour tables do not cover `radius_m`.
+    pub fn alive_node_degrees<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &S,
+        radius_m: f32,
+    ) -> Option<Vec<usize>> {
+        let DistanceCacheMode::NeighbourTables { range_m } = self.mode else {
+            return None;
+        };
+        if radius_m > range_m {
+            return None;
+        }
+
+        let degrees = (0..nodes.node_count())
+            .map(|id| {
+                if !nodes.is_alive(id) {
+                    return 0;
+                }
+                self.neighbours(id)
+                    .iter()
+                    .filter(|&&(neighbour, distance)| distance <= radius_m && nodes.is_alive(neighbour))
+                    .count()
+            })
+            .collect();
+
+        Some(degrees)
+    }
+
+    fn rebuild<S: NodeStore + ?Sized>(&mut self, nodes: &S) {
+        self.invalidate();
+        self.positions = (0..nodes.node_count()).map(|id| nodes.position(id)).collect();
+        let n = self.positions.len();
+
+        match self.mode {
+            DistanceCacheMode::Matrix => {
+                self.matrix.reserve(n * n.saturating_sub(1) / 2);
+                for i in 0..n {
+                    for j in i + 1..n {
+                        self.matrix.push((self.positions[i] - self.positions[j]).length());
+                    }
+                }
+            }
+            DistanceCacheMode::NeighbourTables { range_m } => {
+                let grid = SpatialGrid::new(self.positions.iter().copied().enumerate());
+
+                self.neighbour_offsets.push(0);
+                for (i, &position) in self.positions.iter().enumerate() {
+                    let start = self.neighbours.len();
+                    grid.for_each_within_radius(position, range_m, |j, distance| {
+                        if j != i {
+                            self.neighbours.push((j, distance));
+                        }
+                    });
+                    self.neighbours[start..].sort_unstable_by_key(|&(id, _)| id);
+                    self.neighbour_offsets.push(self.neighbours.len());
+                }
+            }
+        }
+    }
+
+    /// Position of pair (a, b), a ≠ b, in the condensed upper triangle.
+    fn matrix_index(&self, a: usize, b: usize) -> usize {
+        let (i, j) = if a < b { (a, b) } else { (b, a) };
+        let n = self.positions.len();
+        i * n - i * (i + 1) / 2 + (j - i - 1)
+    }
+}
+
+/// Distance between nodes `a` and `b`, from `cache` when available.
+pub fn node_distance<S: NodeStore + ?Sized>(
+    nodes: &S,
+    cache: Option<&DistanceCache>,
+    a: usize,
+    b: usize,
+) -> f32 {
+    match cache {
+        Some(cache) => cache.distance(a, b),
+        None => (nodes.position(a) - nodes.position(b)).length(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+
+    const MODES: [DistanceCacheMode; 2] = [
+        DistanceCacheMode::Matrix,
+        DistanceCacheMode::NeighbourTables { range_m: 25.0 },
+    ];
+
+    /// A 5 × 4 grid of nodes 10 m apart.
+    fn grid_nodes() -> Vec<Node> {
+        (0..20)
+            .map(|id| Node::new(id, Vec2::new((id % 5) as f32 * 10.0, (id / 5) as f32 * 10.0)))
+            .collect()
+    }
+
+    /// Asserts that `cache` holds the current distance of every pair of `nodes`.
+    fn assert_matches(cache: &DistanceCache, nodes: &[Node]) {
+        for a in 0..nodes.len() {
+            for b in 0..nodes.len() {
+                let expected = (nodes[a].position - nodes[b].position).length();
+                assert!((cache.distance(a, b) - expected).abs() < 1e-4, "pair ({a}, {b})");
+            }
+        }
+    }
+
+    #[test]
+    fn cache_is_stale_after_a_node_moves() {
+        for mode in MODES {
+            let mut nodes = grid_nodes();
+            let mut cache = DistanceCache::new(mode, &nodes);
+            assert!(!cache.is_stale(&nodes));
+            assert!(!cache.refresh(&nodes));
+
+            nodes[7].position = Vec2::new(42.0, 3.0);
+            assert!(cache.is_stale(&nodes));
+            // Until refreshed, the cache still reports the old distance
+            assert!((cache.distance(7, 4) - 500.0_f32.sqrt()).abs() < 1e-4);
+
+            assert!(cache.refresh(&nodes));
+            assert!(!cache.is_stale(&nodes));
+            assert_matches(&cache, &nodes);
+        }
+    }
+
+    #[test]
+    fn cache_is_stale_after_a_node_is_added() {
+        for mode in MODES {
+            let mut nodes = grid_nodes();
+            let mut cache = DistanceCache::new(mode, &nodes);
+
+            nodes.push(Node::new(20, Vec2::new(5.0, 5.0)));
+            assert!(cache.is_stale(&nodes));
+
+            assert!(cache.refresh(&nodes));
+            assert_matches(&cache, &nodes);
+        }
+    }
+
+    #[test]
+    fn invalidate_forces_a_rebuild() {
+        for mode in MODES {
+            let nodes = grid_nodes();
+            let mut cache = DistanceCache::new(mode, &nodes);
+
+            cache.invalidate();
+            assert!(cache.is_stale(&nodes));
+            assert!(cache.refresh(&nodes));
+            assert_matches(&cache, &nodes);
+        }
+    }
+
+    #[test]
+    fn neighbour_tables_follow_a_moved_node() {
+        let mut nodes = grid_nodes();
+        let mut cache = DistanceCache::new(DistanceCacheMode::NeighbourTables { range_m: 15.0 }, &nodes);
+        assert!(cache.neighbours(0).iter().any(|&(id, _)| id == 1));
+
+        nodes[0].position = Vec2::new(100.0, 100.0);
+        cache.refresh(&nodes);
+        assert!(cache.neighbours(0).is_empty());
+        assert!(cache.neighbours(1).iter().all(|&(id, _)| id != 0));
+    }
+}
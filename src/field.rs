@@ -0,0 +1,452 @@
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+use rand::Rng;
+
+use crate::columns::NodeStore;
+use crate::config::{
+    DATA_PACKET_SIZE_BITS,
+    FIELD_BACKGROUND_VALUE,
+    FIELD_COMPRESSION_RATIO,
+    FIELD_HOTSPOT_AMPLITUDE,
+    FIELD_HOTSPOT_COUNT,
+    FIELD_HOTSPOT_RADIUS_M,
+    FIELD_HOTSPOT_SPEED_MPS,
+    FIELD_QUANTIZATION_STEP,
+    FIELD_RECONSTRUCTION_GRID_POINTS,
+};
+use crate::metrics::RoundMetrics;
+use crate::simulator::{active_store, Simulator};
+use crate::spatial::SpatialGrid;
+use crate::utils::{calculate_receive_energy, calculate_transmit_energy};
+
+/// A Gaussian bump of the field drifting at constant velocity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hotspot {
+    /// Current center (meters)
+    pub position: Vec2,
+
+    /// Drift velocity (m/s)
+    pub velocity_mps: Vec2,
+
+    /// Peak value added at the center
+    pub amplitude: f32,
+
+    /// Standard deviation of the footprint (meters)
+    pub radius_m: f32,
+}
+
+/// Synthetic environmental field (e.g. a temperature map) over the deployment area.
+///
+/// The value at a point is `background + Σ amplitude · exp(−d² / 2·radius²)` over all
+/// hotspots; hotspots bounce off the edges of the area.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorField {
+    /// Value away from any hotspot
+    pub background: f32,
+
+    /// Moving hotspots
+    pub hotspots: Vec<Hotspot>,
+
+    /// Width of the area the hotspots move in (meters)
+    pub width_m: f32,
+
+    /// Height of the area the hotspots move in (meters)
+    pub height_m: f32,
+}
+
+impl SensorField {
+    /// Places `FIELD_HOTSPOT_COUNT` hotspots at random positions, drifting in
+    /// random directions.
+    pub fn random(width_m: f32, height_m: f32, rng: &mut impl Rng) -> Self {
+        let hotspots = (0..FIELD_HOTSPOT_COUNT)
+            .map(|_| {
+                let position = Vec2::new(rng.random_range(0.0..width_m), rng.random_range(0.0..height_m));
+                let heading = rng.random_range(0.0..TAU);
+                Hotspot {
+                    position,
+                    velocity_mps: Vec2::from_angle(heading) * FIELD_HOTSPOT_SPEED_MPS,
+                    amplitude: FIELD_HOTSPOT_AMPLITUDE,
+                    radius_m: FIELD_HOTSPOT_RADIUS_M,
+                }
+            })
+            .collect();
+
+        Self {
+            background: FIELD_BACKGROUND_VALUE,
+            hotspots,
+            width_m,
+            height_m,
+        }
+    }
+
+    /// Field value at `position`.
+    pub fn value_at(&self, position: Vec2) -> f32 {
+        self.background
+            + self
+                .hotspots
+                .iter()
+                .map(|hotspot| {
+                    let distance_sq = (position - hotspot.position).length_squared();
+                    hotspot.amplitude * (-distance_sq / (2.0 * hotspot.radius_m * hotspot.radius_m)).exp()
+                })
+                .sum::<f32>()
+    }
+
+    /// Moves every hotspot for `duration_s` seconds.
+    pub fn advance(&mut self, duration_s: f32) {
+        let extent = Vec2::new(self.width_m, self.height_m);
+
+        for hotspot in &mut self.hotspots {
+            hotspot.position += hotspot.velocity_mps * duration_s;
+
+            for axis in 0..2 {
+                if hotspot.position[axis] < 0.0 {
+                    hotspot.position[axis] = -hotspot.position[axis];
+                    hotspot.velocity_mps[axis] = -hotspot.velocity_mps[axis];
+                } else if hotspot.position[axis] > extent[axis] {
+                    hotspot.position[axis] = 2.0 * extent[axis] - hotspot.position[axis];
+                    hotspot.velocity_mps[axis] = -hotspot.velocity_mps[axis];
+                }
+            }
+        }
+    }
+}
+
+/// How a cluster head combines the readings of its cluster into what it sends on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    /// One value: the mean of the cluster's readings
+    Mean,
+
+    /// One value: the largest of the cluster's readings
+    Max,
+
+    /// Every reading, quantized; the payload grows with the cluster size
+    Compression {
+        /// Resolution of a compressed reading
+        quantization_step: f32,
+
+        /// Size of a compressed reading relative to a raw data packet
+        compression_ratio: f32,
+    },
+}
+
+impl Aggregation {
+    /// Compression with the default parameters from `config`.
+    pub fn compression() -> Self {
+        Aggregation::Compression {
+            quantization_step: FIELD_QUANTIZATION_STEP,
+            compression_ratio: FIELD_COMPRESSION_RATIO,
+        }
+    }
+}
+
+/// Nodes sample a synthetic field every round; the sink rebuilds the field from
+/// the readings that reach it.
+///
+/// - Every frame, the readings a CH forwards form one aggregate (relays forward
+///   aggregates unchanged); a node sending straight to the sink is its own cluster
+/// - Mean / max aggregates fit into the usual single data packet. A compressed
+///   aggregate needs `n · compression_ratio` data packets for `n` readings; the
+///   payload beyond the first packet is charged on every hop the aggregate crosses
+/// - The sink keeps the latest estimate it received for every node (stale for
+///   nodes whose readings stopped arriving) and reconstructs the field by
+///   nearest-neighbour interpolation between those nodes
+/// - Reconstruction error is measured on a regular grid over the area
+#[derive(Debug, Clone)]
+pub struct FieldSensing {
+    /// Ground truth
+    pub field: SensorField,
+
+    /// Aggregation applied by every cluster head
+    pub aggregation: Aggregation,
+
+    /// Points per side of the reconstruction grid
+    pub grid_points: usize,
+
+    /// The sink's latest estimate of every node's reading (`None` → never received)
+    sink_estimates: Vec<Option<f32>>,
+}
+
+impl FieldSensing {
+    /// Creates a field sensing setup on which the sink has no data yet.
+    pub fn new(field: SensorField, aggregation: Aggregation) -> Self {
+        Self {
+            field,
+            aggregation,
+            grid_points: FIELD_RECONSTRUCTION_GRID_POINTS,
+            sink_estimates: Vec::new(),
+        }
+    }
+
+    /// The sink's latest estimate of node `id`'s reading.
+    pub fn sink_estimate(&self, id: usize) -> Option<f32> {
+        self.sink_estimates.get(id).copied().flatten()
+    }
+
+    /// The sink's reconstruction of the field at `position` (`None` before any
+    /// reading arrived).
+    pub fn reconstruct_at<S: NodeStore + ?Sized>(&self, nodes: &S, position: Vec2) -> Option<f32> {
+        self.sample_grid(nodes)
+            .nearest(position)
+            .and_then(|(id, _)| self.sink_estimate(id))
+    }
+
+    /// Root-mean-square error of the sink's reconstruction over the grid (NaN
+    /// before any reading arrived).
+    pub fn reconstruction_rmse<S: NodeStore + ?Sized>(&self, nodes: &S) -> f32 {
+        let samples = self.sample_grid(nodes);
+        if samples.is_empty() {
+            return f32::NAN;
+        }
+
+        let grid_points = self.grid_points.max(1);
+        let cell = Vec2::new(self.field.width_m, self.field.height_m) / grid_points as f32;
+        let mut squared_error = 0.0;
+        let mut point_count = 0;
+
+        for i in 0..grid_points {
+            for j in 0..grid_points {
+                let point = (Vec2::new(i as f32, j as f32) + 0.5) * cell;
+                if let Some(estimate) = samples.nearest(point).and_then(|(id, _)| self.sink_estimate(id)) {
+                    squared_error += (estimate - self.field.value_at(point)).powi(2);
+                    point_count += 1;
+                }
+            }
+        }
+
+        (squared_error / point_count as f32).sqrt()
+    }
+
+    /// Bits an aggregate of `origin_ids` needs beyond the single data packet every
+    /// transmission carries (only compressed aggregates grow with their readings).
+    pub fn extra_payload_bits(&self, origin_ids: &[usize]) -> f32 {
+        match self.aggregation {
+            Aggregation::Mean | Aggregation::Max => 0.0,
+            Aggregation::Compression { compression_ratio, .. } => {
+                let payload_bits = origin_ids.len() as f32 * DATA_PACKET_SIZE_BITS * compression_ratio;
+                (payload_bits - DATA_PACKET_SIZE_BITS).max(0.0)
+            }
+        }
+    }
+
+    /// Updates the sink's estimates from one aggregate that reached it; `origin_ids`
+    /// are the readings the forwarding CH combined.
+    pub(crate) fn receive_aggregate<S: NodeStore + ?Sized>(&mut self, nodes: &S, origin_ids: &[usize]) {
+        if origin_ids.is_empty() {
+            return;
+        }
+        self.sink_estimates.resize(self.sink_estimates.len().max(nodes.node_count()), None);
+
+        let readings: Vec<f32> = origin_ids
+            .iter()
+            .map(|&id| self.field.value_at(nodes.position(id)))
+            .collect();
+
+        match self.aggregation {
+            Aggregation::Mean => {
+                let mean = readings.iter().sum::<f32>() / readings.len() as f32;
+                for &id in origin_ids {
+                    self.sink_estimates[id] = Some(mean);
+                }
+            }
+            Aggregation::Max => {
+                let max = readings.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                for &id in origin_ids {
+                    self.sink_estimates[id] = Some(max);
+                }
+            }
+            Aggregation::Compression { quantization_step, .. } => {
+                for (&id, &reading) in origin_ids.iter().zip(&readings) {
+                    self.sink_estimates[id] = Some(quantize(reading, quantization_step));
+                }
+            }
+        }
+    }
+
+    /// Records the reconstruction error after this round's deliveries and moves the
+    /// field on by one round.
+    pub(crate) fn record_round<S: NodeStore + ?Sized>(
+        &mut self,
+        nodes: &S,
+        round_duration_s: f32,
+        metrics: &mut RoundMetrics,
+    ) {
+        metrics.field_rmse = self.reconstruction_rmse(nodes);
+        self.field.advance(round_duration_s);
+    }
+
+    /// Nodes the sink holds an estimate for, indexed by node id.
+    fn sample_grid<S: NodeStore + ?Sized>(&self, nodes: &S) -> SpatialGrid {
+        SpatialGrid::new(
+            (0..nodes.node_count().min(self.sink_estimates.len()))
+                .filter(|&id| self.sink_estimates[id].is_some())
+                .map(|id| (id, nodes.position(id))),
+        )
+    }
+}
+
+/// Rounds `value` to the nearest multiple of `step` (unchanged for `step <= 0`).
+fn quantize(value: f32, step: f32) -> f32 {
+    if step > 0.0 { (value / step).round() * step } else { value }
+}
+
+/// Charges the extra payload of the aggregates sent over one hop: TX for `sender_id`
+/// on every attempt and, if another node receives it, RX for `receiver_id`.
+pub(crate) fn charge_extra_payload<S: NodeStore + ?Sized>(
+    nodes: &mut S,
+    sender_id: usize,
+    receiver_id: Option<usize>,
+    distance: f32,
+    attempts: usize,
+    extra_bits: f32,
+    metrics: &mut RoundMetrics,
+) {
+    if extra_bits <= 0.0 {
+        return;
+    }
+
+    let transmit_energy = calculate_transmit_energy(extra_bits, distance) * attempts as f32;
+    nodes.consume_energy(sender_id, transmit_energy);
+    metrics.compression_energy_j += transmit_energy;
+
+    if let Some(receiver_id) = receiver_id {
+        let receive_energy = calculate_receive_energy(extra_bits) * attempts as f32;
+        nodes.consume_energy(receiver_id, receive_energy);
+        metrics.compression_energy_j += receive_energy;
+    }
+}
+
+/// Runs the sink's field reconstruction for the round that just ended.
+pub(crate) fn sense_field(simulator: &mut Simulator) {
+    let round_duration_s = simulator.round_duration_s();
+    let Simulator { nodes, columns, metrics, field, .. } = simulator;
+
+    if let Some(field) = field {
+        field.record_round(active_store(nodes, columns), round_duration_s, metrics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+
+    /// A 100 m × 100 m field with background 20 and one still hotspot of +10 at (50, 50).
+    fn one_hotspot_field() -> SensorField {
+        SensorField {
+            background: 20.0,
+            hotspots: vec![Hotspot {
+                position: Vec2::new(50.0, 50.0),
+                velocity_mps: Vec2::ZERO,
+                amplitude: 10.0,
+                radius_m: 10.0,
+            }],
+            width_m: 100.0,
+            height_m: 100.0,
+        }
+    }
+
+    /// Nodes at the given positions.
+    fn nodes_at(positions: &[(f32, f32)]) -> Vec<Node> {
+        positions
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| Node::new(id, Vec2::new(x, y)))
+            .collect()
+    }
+
+    #[test]
+    fn field_value_peaks_at_hotspots() {
+        let field = one_hotspot_field();
+        assert!((field.value_at(Vec2::new(50.0, 50.0)) - 30.0).abs() < 1e-5);
+        // One standard deviation away: 10 · e^(−1/2)
+        assert!((field.value_at(Vec2::new(60.0, 50.0)) - (20.0 + 10.0 * (-0.5f32).exp())).abs() < 1e-5);
+        assert!((field.value_at(Vec2::new(0.0, 0.0)) - 20.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hotspots_bounce_off_the_edges() {
+        let mut field = one_hotspot_field();
+        field.hotspots[0].position = Vec2::new(95.0, 3.0);
+        field.hotspots[0].velocity_mps = Vec2::new(2.0, -1.0);
+
+        field.advance(5.0);
+        assert!((field.hotspots[0].position - Vec2::new(95.0, 2.0)).length() < 1e-5);
+        assert_eq!(field.hotspots[0].velocity_mps, Vec2::new(-2.0, 1.0));
+    }
+
+    #[test]
+    fn sink_estimates_follow_the_aggregation() {
+        let nodes = nodes_at(&[(50.0, 50.0), (0.0, 0.0), (100.0, 100.0)]);
+        let aggregations = [
+            (Aggregation::Mean, [Some(70.0 / 3.0); 3]),
+            (Aggregation::Max, [Some(30.0); 3]),
+            (
+                Aggregation::Compression { quantization_step: 4.0, compression_ratio: 0.5 },
+                [Some(32.0), Some(20.0), Some(20.0)],
+            ),
+        ];
+
+        for (aggregation, expected) in aggregations {
+            let mut sensing = FieldSensing::new(one_hotspot_field(), aggregation);
+            assert_eq!(sensing.sink_estimate(0), None);
+
+            sensing.receive_aggregate(&nodes, &[0, 1, 2]);
+            for (id, expected) in expected.iter().enumerate() {
+                let estimate = sensing.sink_estimate(id).unwrap();
+                assert!((estimate - expected.unwrap()).abs() < 1e-3, "{aggregation:?}, node {id}");
+            }
+        }
+    }
+
+    #[test]
+    fn compressed_aggregates_grow_with_their_readings() {
+        let compression = FieldSensing::new(
+            one_hotspot_field(),
+            Aggregation::Compression { quantization_step: 1.0, compression_ratio: 0.25 },
+        );
+        assert_eq!(compression.extra_payload_bits(&[0, 1, 2, 3]), 0.0);
+        assert_eq!(compression.extra_payload_bits(&[0; 8]), DATA_PACKET_SIZE_BITS);
+
+        let mean = FieldSensing::new(one_hotspot_field(), Aggregation::Mean);
+        assert_eq!(mean.extra_payload_bits(&[0; 8]), 0.0);
+    }
+
+    #[test]
+    fn reconstruction_uses_the_nearest_reporting_node() {
+        let mut field = one_hotspot_field();
+        field.hotspots.clear();
+        let nodes = nodes_at(&[(25.0, 50.0), (75.0, 50.0)]);
+        let mut sensing = FieldSensing::new(field, Aggregation::Mean);
+
+        assert!(sensing.reconstruction_rmse(&nodes).is_nan());
+        assert_eq!(sensing.reconstruct_at(&nodes, Vec2::new(10.0, 10.0)), None);
+
+        // A flat field is rebuilt exactly from a single reading
+        sensing.receive_aggregate(&nodes, &[1]);
+        assert_eq!(sensing.reconstruct_at(&nodes, Vec2::new(10.0, 10.0)), Some(20.0));
+        assert_eq!(sensing.reconstruction_rmse(&nodes), 0.0);
+    }
+
+    #[test]
+    fn extra_payload_is_charged_per_attempt() {
+        let mut nodes = nodes_at(&[(0.0, 0.0), (30.0, 0.0)]);
+        for node in &mut nodes {
+            node.remaining_energy_j = 1.0e-3;
+        }
+        let mut metrics = RoundMetrics::default();
+        charge_extra_payload(&mut nodes, 0, Some(1), 30.0, 3, 1000.0, &mut metrics);
+
+        let transmit_energy = 3.0 * calculate_transmit_energy(1000.0, 30.0);
+        let receive_energy = 3.0 * calculate_receive_energy(1000.0);
+        assert!((1.0e-3 - nodes[0].remaining_energy_j - transmit_energy).abs() < 1e-9);
+        assert!((1.0e-3 - nodes[1].remaining_energy_j - receive_energy).abs() < 1e-9);
+        assert!((metrics.compression_energy_j - transmit_energy - receive_energy).abs() < 1e-9);
+
+        // The sink receives for free
+        charge_extra_payload(&mut nodes, 1, None, 30.0, 1, 1000.0, &mut metrics);
+        assert!((1.0e-3 - nodes[1].remaining_energy_j - receive_energy - transmit_energy / 3.0).abs() < 1e-9);
+    }
+}
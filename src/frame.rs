@@ -0,0 +1,104 @@
+use crate::config::DATA_PACKET_SIZE_BITS;
+use crate::distance::node_distance;
+use crate::link::send_to_sink;
+use crate::simulator::{active_store, Simulator};
+use crate::utils::calculate_transmit_energy;
+
+/// Starts another steady-state frame of the current round (every frame after the first).
+///
+/// The cluster structure, schedules and routing choices of the setup phase are kept;
+/// only the data of the new frame is sent:
+/// - Nodes depleted during the previous frames are retired and stop sensing
+/// - Every other alive node senses a new reading
+/// - Members send it to their CH (the CH pays RX later, in `collect_member_data`);
+///   if their CH was retired the reading is lost
+/// - `direct_ids` (nodes that sent straight to the sink in the first frame) do so again
+/// - Remaining nodes (isolated, without a CH, or that could not join) drop their reading
+pub(crate) fn start_data_frame(simulator: &mut Simulator, direct_ids: &[usize]) {
+    let Simulator { nodes, columns, alive_node_count, distance_cache, link, rng, metrics, field, .. } = simulator;
+    let nodes = active_store(nodes, columns);
+
+    for node_id in 0..nodes.node_count() {
+        if nodes.is_alive(node_id) && nodes.remaining_energy_j(node_id) <= 0.0 {
+            nodes.set_alive(node_id, false);
+            *alive_node_count -= 1;
+        }
+    }
+    metrics.record_generated_readings(nodes);
+
+    let mut sends_direct = vec![false; nodes.node_count()];
+    for &id in direct_ids {
+        sends_direct[id] = true;
+    }
+
+    for (node_id, sends_direct) in sends_direct.into_iter().enumerate() {
+        if !nodes.is_alive(node_id) || nodes.is_cluster_head(node_id) {
+            continue;
+        }
+
+        if let Some(ch_id) = nodes.cluster_head_id(node_id) {
+            let distance = node_distance(nodes, distance_cache.as_ref(), node_id, ch_id);
+            nodes.consume_energy(node_id, calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance));
+            metrics.record_packets(node_id, |packets| packets.sent_to_cluster_head += 1);
+
+            if !nodes.is_alive(ch_id) {
+                metrics.lost_packets += 1;
+                metrics.record_dropped_readings(&[node_id]);
+            }
+        } else if sends_direct && !nodes.is_isolated(node_id) {
+            send_to_sink(nodes, node_id, &[&[node_id]], link, rng, metrics, field.as_mut());
+        } else {
+            metrics.record_dropped_readings(&[node_id]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leach::Leach;
+    use crate::metrics::PacketCounters;
+    use crate::simulator::{Protocol, Simulator};
+    use crate::zcr::Zcr;
+
+    /// Packet counts of the first round with `frames` steady-state frames.
+    fn first_round_packets(protocol: &mut impl Protocol, frames: usize) -> PacketCounters {
+        let mut simulator = Simulator::with_seed(300.0, 300.0, 80, 13).with_frames_per_round(frames);
+        simulator.update(protocol);
+        simulator.metrics.packets
+    }
+
+    #[test]
+    fn every_frame_repeats_the_data_phase() {
+        for frames in [2, 3] {
+            let leach = first_round_packets(&mut Leach::new(0.1), 1);
+            let leach_frames = first_round_packets(&mut Leach::new(0.1), frames);
+            let zcr = first_round_packets(&mut Zcr::new(0.1), 1);
+            let zcr_frames = first_round_packets(&mut Zcr::new(0.1), frames);
+
+            for (one, many) in [(leach, leach_frames), (zcr, zcr_frames)] {
+                assert_eq!(one.generated, 80);
+                assert_eq!(many.generated, frames * one.generated);
+                assert_eq!(many.sent_to_cluster_head, frames * one.sent_to_cluster_head);
+                assert_eq!(many.sent_to_base_station, frames * one.sent_to_base_station);
+                assert_eq!(many.delivered, frames * one.delivered);
+            }
+        }
+    }
+
+    #[test]
+    fn frames_share_one_cluster_setup() {
+        let energy_after = |frames: usize| {
+            let mut simulator = Simulator::with_seed(300.0, 300.0, 80, 13).with_frames_per_round(frames);
+            simulator.update(&mut Leach::new(0.1));
+            simulator.nodes().iter().map(|node| node.remaining_energy_j).collect::<Vec<f32>>()
+        };
+        let (one, two, three) = (energy_after(1), energy_after(2), energy_after(3));
+
+        // Every extra frame costs each node the same amount: the setup is paid once
+        for id in 0..80 {
+            let second = one[id] - two[id];
+            let third = two[id] - three[id];
+            assert!((second - third).abs() < 1e-6, "node {id}: {second} vs {third}");
+        }
+    }
+}
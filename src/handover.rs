@@ -0,0 +1,308 @@
+use crate::config::{
+    CONTROL_PACKET_SIZE_BITS,
+    DATA_PACKET_SIZE_BITS,
+    HANDOVER_ENERGY_THRESHOLD_J,
+};
+use crate::columns::NodeStore;
+use crate::distance::{node_distance, DistanceCache};
+use crate::mac::TdmaFrame;
+use crate::simulator::{active_store, Simulator};
+use crate::utils::{
+    calculate_aggregation_energy,
+    calculate_receive_energy,
+    calculate_transmit_energy,
+};
+
+/// Settings for mid-round cluster-head handover.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandoverConfig {
+    /// Residual energy below which a CH passes its role on (Joules)
+    pub energy_threshold_j: f32,
+
+    /// Size of each handover control message (bits)
+    pub control_packet_bits: f32,
+}
+
+impl Default for HandoverConfig {
+    fn default() -> Self {
+        Self {
+            energy_threshold_j: HANDOVER_ENERGY_THRESHOLD_J,
+            control_packet_bits: CONTROL_PACKET_SIZE_BITS,
+        }
+    }
+}
+
+/// Receives and aggregates the data of every member of `ch_id`, one packet at a time.
+///
+/// - Every member packet crosses the simulator's link layer: the member pays TX for
+///   retransmissions (the first attempt is charged when it joins, towards `ch_id`,
+///   and moved to the receiving CH after a handover) and the CH pays RX for every
+///   attempt; a packet lost on the link is dropped without aggregation.
+/// - With a `tdma` frame, a member packet marked as collided is received (CH pays RX)
+///   but dropped and counted in `metrics.data_collisions`.
+/// - Without `handover`, the CH receives and forwards every packet even if that
+///   drains it below zero; it is retired at the start of the next round.
+/// - With `handover` set, a CH whose energy falls below the threshold passes its role
+///   (and the data aggregated so far) to the best-scoring member before it dies.
+///   A CH that runs out of energy anyway stops receiving; every packet it held or
+///   had yet to receive is counted in `metrics.lost_packets`, and the readings they
+///   carried (including the CH's own) are counted as dropped.
+///
+/// `score(nodes, id)` ranks the members that could take over.
+///
+/// Returns the node that ends the round as CH and must forward the aggregate,
+/// together with the ids of the nodes whose readings the aggregate carries,
+/// or `None` if the CH died without handing over (only with `handover` set).
+pub fn collect_member_data(
+    simulator: &mut Simulator,
+    ch_id: usize,
+    handover: Option<&HandoverConfig>,
+    score: &dyn Fn(&dyn NodeStore, usize) -> f32,
+    tdma: Option<&TdmaFrame>,
+) -> Option<(usize, Vec<usize>)> {
+    let Simulator { nodes, columns, distance_cache, link, rng, metrics, .. } = simulator;
+    let nodes = active_store(nodes, columns);
+    let distances = distance_cache.as_ref();
+    let members = nodes.cluster_member_ids(ch_id).to_vec();
+    let receive_energy = calculate_receive_energy(DATA_PACKET_SIZE_BITS);
+    let aggregation_energy = calculate_aggregation_energy(DATA_PACKET_SIZE_BITS);
+
+    let mut current_ch_id = ch_id;
+    let mut held_packets = 0usize;
+    let mut reading_origin_ids = vec![ch_id];
+    let mut ch_dead = false;
+
+    for (i, &member_id) in members.iter().enumerate() {
+        // Retired in an earlier frame of this round
+        if !nodes.is_alive(member_id) {
+            continue;
+        }
+
+        if ch_dead {
+            metrics.lost_packets += 1;
+            metrics.record_dropped_readings(&[member_id]);
+            continue;
+        }
+
+        if let Some(config) = handover
+            && nodes.remaining_energy_j(current_ch_id) < config.energy_threshold_j
+            && let Some(new_ch_id) =
+                hand_over(nodes, distances, current_ch_id, &members[i..], held_packets, config, score)
+        {
+            current_ch_id = new_ch_id;
+            metrics.cluster_head_handovers += 1;
+        }
+
+        // The first attempt was charged towards `ch_id` when the frame started;
+        // after a handover it goes to the current CH instead (or nowhere, for the
+        // member that took over the CH role and keeps its reading locally)
+        if current_ch_id != ch_id {
+            let charged_distance = node_distance(nodes, distances, member_id, ch_id);
+            let mut energy = -calculate_transmit_energy(DATA_PACKET_SIZE_BITS, charged_distance);
+            if member_id != current_ch_id {
+                let distance = node_distance(nodes, distances, member_id, current_ch_id);
+                energy += calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance);
+            }
+            nodes.consume_energy(member_id, energy);
+        }
+
+        if member_id == current_ch_id {
+            reading_origin_ids.push(member_id);
+            continue;
+        }
+
+        if tdma.is_some_and(|frame| frame.is_collided(member_id)) {
+            // Garbled by a transmitter of another cluster in the same slot
+            nodes.consume_energy(current_ch_id, receive_energy);
+            metrics.data_collisions += 1;
+            metrics.record_dropped_readings(&[member_id]);
+        } else {
+            let distance = node_distance(nodes, distances, member_id, current_ch_id);
+            let outcome = link.transmit(distance, rng);
+            metrics.record_link_outcome(member_id, outcome);
+
+            if outcome.attempts > 1 {
+                nodes.consume_energy(
+                    member_id,
+                    calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance) * outcome.retransmissions() as f32,
+                );
+            }
+
+            if outcome.delivered {
+                nodes.consume_energy(
+                    current_ch_id,
+                    receive_energy * outcome.attempts as f32 + aggregation_energy,
+                );
+                held_packets += 1;
+                reading_origin_ids.push(member_id);
+                metrics.record_packets(current_ch_id, |packets| packets.aggregated += 1);
+            } else {
+                nodes.consume_energy(current_ch_id, receive_energy * outcome.attempts as f32);
+                metrics.record_dropped_readings(&[member_id]);
+            }
+        }
+
+        if handover.is_some() && nodes.remaining_energy_j(current_ch_id) <= 0.0 {
+            metrics.lost_packets += held_packets;
+            metrics.record_dropped_readings(&reading_origin_ids);
+            held_packets = 0;
+            ch_dead = true;
+        }
+    }
+
+    if ch_dead {
+        return None;
+    }
+
+    // Hand over before the final transmission if the CH is now too weak
+    if let Some(config) = handover
+        && nodes.remaining_energy_j(current_ch_id) < config.energy_threshold_j
+        && let Some(new_ch_id) = hand_over(nodes, distances, current_ch_id, &[], held_packets, config, score)
+    {
+        current_ch_id = new_ch_id;
+        metrics.cluster_head_handovers += 1;
+    }
+
+    Some((current_ch_id, reading_origin_ids))
+}
+
+/// Passes the CH role of `old_ch_id` to its best-scoring member whose energy is
+/// above the handover threshold.
+///
+/// Energy charged:
+/// - old CH → new CH: control message (+ aggregated data if any packets are held)
+/// - new CH → `pending_members`: control broadcast announcing the new CH
+/// - pending members: RX of the announcement (`collect_member_data` moves their data
+///   TX over to the new CH when they send)
+///
+/// Returns the new CH id, or `None` if no member can take over.
+fn hand_over(
+    nodes: &mut dyn NodeStore,
+    distances: Option<&DistanceCache>,
+    old_ch_id: usize,
+    pending_members: &[usize],
+    held_packets: usize,
+    config: &HandoverConfig,
+    score: &dyn Fn(&dyn NodeStore, usize) -> f32,
+) -> Option<usize> {
+    let new_ch_id = nodes
+        .cluster_member_ids(old_ch_id)
+        .iter()
+        .copied()
+        .filter(|&id| nodes.is_alive(id) && nodes.remaining_energy_j(id) > config.energy_threshold_j)
+        .max_by(|&a, &b| score(nodes, a).total_cmp(&score(nodes, b)))?;
+
+    // Old CH passes control (and the partial aggregate) to the new CH
+    let mut transfer_bits = config.control_packet_bits;
+    if held_packets > 0 {
+        transfer_bits += DATA_PACKET_SIZE_BITS;
+    }
+    let transfer_distance = node_distance(nodes, distances, old_ch_id, new_ch_id);
+    nodes.consume_energy(old_ch_id, calculate_transmit_energy(transfer_bits, transfer_distance));
+    nodes.consume_energy(new_ch_id, calculate_receive_energy(transfer_bits));
+
+    // New CH announces itself to the members that have not transmitted yet
+    let mut broadcast_radius: f32 = 0.0;
+    for &member_id in pending_members {
+        if member_id == new_ch_id {
+            continue;
+        }
+
+        broadcast_radius = broadcast_radius.max(node_distance(nodes, distances, member_id, new_ch_id));
+        nodes.consume_energy(member_id, calculate_receive_energy(config.control_packet_bits));
+    }
+    if broadcast_radius > 0.0 {
+        nodes.consume_energy(new_ch_id, calculate_transmit_energy(config.control_packet_bits, broadcast_radius));
+    }
+
+    // Move the cluster over to the new CH; the old CH becomes a member
+    nodes.transfer_cluster_head(old_ch_id, new_ch_id);
+
+    Some(new_ch_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec2;
+
+    /// CH 0 at the origin with members 1 (10 m east), 2 (10 m north) and 3 (20 m east);
+    /// member 2 holds the most energy.
+    fn cluster(ch_energy_j: f32) -> Simulator {
+        let mut simulator = Simulator::with_seed(100.0, 100.0, 0, 1);
+        for position in [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0), (20.0, 0.0)] {
+            simulator.add_node(Vec2::from(position));
+        }
+
+        let nodes = simulator.node_store_mut();
+        nodes.promote_to_cluster_head(0, 1);
+        for member_id in 1..=3 {
+            nodes.join_cluster(member_id, 0);
+        }
+        nodes.finish_cluster_formation();
+        nodes.set_remaining_energy_j(0, ch_energy_j);
+        nodes.set_remaining_energy_j(1, 1.0);
+        nodes.set_remaining_energy_j(3, 1.0);
+        simulator
+    }
+
+    fn by_energy(nodes: &dyn NodeStore, id: usize) -> f32 {
+        nodes.remaining_energy_j(id)
+    }
+
+    #[test]
+    fn weak_cluster_head_hands_over_to_best_member() {
+        let mut simulator = cluster(0.005);
+        let result = collect_member_data(&mut simulator, 0, Some(&HandoverConfig::default()), &by_energy, None);
+
+        let (ch_id, mut reading_origin_ids) = result.unwrap();
+        reading_origin_ids.sort_unstable();
+        assert_eq!(ch_id, 2);
+        assert_eq!(reading_origin_ids, [0, 1, 2, 3]);
+        assert_eq!(simulator.metrics.cluster_head_handovers, 1);
+
+        let nodes = simulator.node_store();
+        assert!(nodes.is_cluster_head(2) && !nodes.is_cluster_head(0));
+        assert_eq!(nodes.cluster_member_ids(2), [1, 3, 0]);
+        assert!((0..4).filter(|&id| id != 2).all(|id| nodes.cluster_head_id(id) == Some(2)));
+    }
+
+    #[test]
+    fn members_after_a_handover_pay_for_the_new_hop() {
+        let mut simulator = cluster(0.005);
+        collect_member_data(&mut simulator, 0, Some(&HandoverConfig::default()), &by_energy, None);
+
+        // Member 3 was charged 20 m towards the old CH; it sends √500 m to the new
+        // one and listened to its announcement
+        let expected_j = 1.0
+            - (calculate_transmit_energy(DATA_PACKET_SIZE_BITS, 500.0_f32.sqrt())
+                - calculate_transmit_energy(DATA_PACKET_SIZE_BITS, 20.0))
+            - calculate_receive_energy(CONTROL_PACKET_SIZE_BITS);
+        assert!((simulator.node_store().remaining_energy_j(3) - expected_j).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cluster_head_without_handover_drains_below_zero() {
+        let mut simulator = cluster(1.0e-4);
+        let result = collect_member_data(&mut simulator, 0, None, &by_energy, None);
+
+        assert_eq!(result.map(|(ch_id, _)| ch_id), Some(0));
+        assert!(simulator.node_store().remaining_energy_j(0) < 0.0);
+        assert_eq!(simulator.metrics.lost_packets, 0);
+    }
+
+    #[test]
+    fn cluster_head_without_successor_loses_its_packets() {
+        let mut simulator = cluster(1.0e-4);
+        for member_id in 1..=3 {
+            simulator.node_store_mut().set_remaining_energy_j(member_id, 0.005);
+        }
+        let result = collect_member_data(&mut simulator, 0, Some(&HandoverConfig::default()), &by_energy, None);
+
+        // The CH dies while receiving member 1's packet: that packet and the two
+        // members still to send are lost
+        assert!(result.is_none());
+        assert_eq!(simulator.metrics.cluster_head_handovers, 0);
+        assert_eq!(simulator.metrics.lost_packets, 3);
+    }
+}
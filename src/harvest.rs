@@ -0,0 +1,308 @@
+use std::f32::consts::TAU;
+
+use rand::Rng;
+
+use crate::config::{
+    HARVEST_CLOUD_PROBABILITY,
+    HARVEST_CLOUD_TRANSMISSION,
+    HARVEST_CONSTANT_POWER_W,
+    HARVEST_DAY_LENGTH_S,
+    HARVEST_PEAK_POWER_W,
+    HARVEST_REVIVAL_THRESHOLD_J,
+    INITIAL_NODE_ENERGY_J,
+};
+use crate::simulator::{active_store, Simulator};
+
+/// Harvesting power available to a full-size harvester over time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HarvestProfile {
+    /// Same power at all times
+    Constant {
+        /// Harvesting power (W)
+        power_w: f32,
+    },
+
+    /// Solar cycle: `peak_power_w · max(0, sin(2π t / day_length_s))`, so the
+    /// simulation starts at sunrise and the second half of every day is night
+    DayNight {
+        /// Harvesting power at solar noon (W)
+        peak_power_w: f32,
+
+        /// Length of one day/night cycle (seconds)
+        day_length_s: f32,
+    },
+
+    /// Solar cycle under a random sky: every round is overcast with probability
+    /// `cloud_probability`, and an overcast round lets a uniformly drawn fraction
+    /// in `[0, cloud_transmission]` of the sunlight through
+    Cloudy {
+        /// Harvesting power at solar noon under a clear sky (W)
+        peak_power_w: f32,
+
+        /// Length of one day/night cycle (seconds)
+        day_length_s: f32,
+
+        /// Probability that a round is overcast
+        cloud_probability: f32,
+
+        /// Largest fraction of the sunlight that gets through the clouds
+        cloud_transmission: f32,
+    },
+}
+
+impl HarvestProfile {
+    /// Constant profile with the default power from `config`.
+    pub fn constant() -> Self {
+        HarvestProfile::Constant {
+            power_w: HARVEST_CONSTANT_POWER_W,
+        }
+    }
+
+    /// Day/night profile with the default parameters from `config`.
+    pub fn day_night() -> Self {
+        HarvestProfile::DayNight {
+            peak_power_w: HARVEST_PEAK_POWER_W,
+            day_length_s: HARVEST_DAY_LENGTH_S,
+        }
+    }
+
+    /// Cloudy profile with the default parameters from `config`.
+    pub fn cloudy() -> Self {
+        HarvestProfile::Cloudy {
+            peak_power_w: HARVEST_PEAK_POWER_W,
+            day_length_s: HARVEST_DAY_LENGTH_S,
+            cloud_probability: HARVEST_CLOUD_PROBABILITY,
+            cloud_transmission: HARVEST_CLOUD_TRANSMISSION,
+        }
+    }
+
+    /// Harvesting power at time `time_s` under a clear sky (W).
+    fn clear_sky_power_w(&self, time_s: f32) -> f32 {
+        match *self {
+            HarvestProfile::Constant { power_w } => power_w,
+            HarvestProfile::DayNight { peak_power_w, day_length_s }
+            | HarvestProfile::Cloudy { peak_power_w, day_length_s, .. } => {
+                if day_length_s <= 0.0 {
+                    return peak_power_w;
+                }
+                let phase = (time_s / day_length_s).fract();
+                peak_power_w * (TAU * phase).sin().max(0.0)
+            }
+        }
+    }
+
+    /// Mean fraction of the clear-sky power that reaches the harvester.
+    fn expected_sky_factor(&self) -> f32 {
+        match *self {
+            HarvestProfile::Cloudy { cloud_probability, cloud_transmission, .. } => {
+                1.0 - cloud_probability + cloud_probability * cloud_transmission / 2.0
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Fraction of the clear-sky power that reaches the harvester in one round.
+    ///
+    /// Draws from `rng` only for the cloudy profile.
+    fn sample_sky_factor(&self, rng: &mut impl Rng) -> f32 {
+        let HarvestProfile::Cloudy { cloud_probability, cloud_transmission, .. } = *self else {
+            return 1.0;
+        };
+
+        if rng.random::<f32>() < cloud_probability {
+            rng.random::<f32>() * cloud_transmission
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Energy harvester fitted to every node, scaled per node by `Node::harvest_scale`
+/// (1 by default; see `Simulator::with_harvest_scales`).
+///
+/// - Round `r` covers the time `[(r − 1)·T, r·T]` for a round duration `T`; the
+///   profile is sampled at the middle of the round
+/// - Harvested energy is stored only up to `capacity_j`
+/// - A dead node rejoins the network once its stored energy reaches
+///   `revival_threshold_j`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyHarvester {
+    /// Harvesting power over time
+    pub profile: HarvestProfile,
+
+    /// Most energy a node can store (Joules)
+    pub capacity_j: f32,
+
+    /// Stored energy a dead node needs before it is revived (Joules)
+    pub revival_threshold_j: f32,
+}
+
+impl Default for EnergyHarvester {
+    fn default() -> Self {
+        Self::new(HarvestProfile::constant())
+    }
+}
+
+impl EnergyHarvester {
+    /// Creates a harvester whose storage holds the initial node energy.
+    pub fn new(profile: HarvestProfile) -> Self {
+        Self {
+            profile,
+            capacity_j: INITIAL_NODE_ENERGY_J,
+            revival_threshold_j: HARVEST_REVIVAL_THRESHOLD_J,
+        }
+    }
+
+    /// Caps the energy a node can store at `capacity_j`.
+    pub fn with_capacity(mut self, capacity_j: f32) -> Self {
+        self.capacity_j = capacity_j;
+        self
+    }
+
+    /// Revives dead nodes once they stored `revival_threshold_j`.
+    pub fn with_revival_threshold(mut self, revival_threshold_j: f32) -> Self {
+        self.revival_threshold_j = revival_threshold_j;
+        self
+    }
+
+    /// Energy a full-size harvester collects during `round` (Joules).
+    pub fn round_energy_j(&self, round: usize, round_duration_s: f32, rng: &mut impl Rng) -> f32 {
+        self.clear_sky_energy_j(round, round_duration_s) * self.profile.sample_sky_factor(rng)
+    }
+
+    /// Energy a full-size harvester is expected to collect during `round`
+    /// (Joules); the forecast knows the solar cycle but not the clouds.
+    pub fn predicted_energy_j(&self, round: usize, round_duration_s: f32) -> f32 {
+        self.clear_sky_energy_j(round, round_duration_s) * self.profile.expected_sky_factor()
+    }
+
+    fn clear_sky_energy_j(&self, round: usize, round_duration_s: f32) -> f32 {
+        let mid_round_s = (round as f32 - 0.5).max(0.0) * round_duration_s;
+        self.profile.clear_sky_power_w(mid_round_s) * round_duration_s
+    }
+}
+
+/// Adds this round's harvested energy to every node (after the round's draw).
+///
+/// - Nodes that ran out of energy this round are retired first
+/// - Stored energy never drops below 0 nor exceeds the harvester's capacity (or
+///   the battery's, with a battery model)
+/// - Dead nodes whose stored energy reached the revival threshold are alive again
+pub(crate) fn harvest_round(simulator: &mut Simulator) {
+    let round_duration_s = simulator.round_duration_s();
+    let Simulator {
+        nodes,
+        columns,
+        alive_node_count,
+        current_round,
+        rng,
+        metrics,
+        battery,
+        harvester,
+        ..
+    } = simulator;
+    let Some(harvester) = harvester else {
+        return;
+    };
+    let nodes = active_store(nodes, columns);
+
+    let unit_energy_j = harvester.round_energy_j(*current_round, round_duration_s, rng);
+
+    for id in 0..nodes.node_count() {
+        if nodes.is_alive(id) && nodes.remaining_energy_j(id) <= 0.0 {
+            nodes.set_alive(id, false);
+            *alive_node_count -= 1;
+        }
+
+        let stored_j = battery
+            .as_ref()
+            .and_then(|battery| battery.stored_j(id))
+            .unwrap_or(nodes.remaining_energy_j(id))
+            .max(0.0);
+        let energy_j = (unit_energy_j * nodes.harvest_scale(id))
+            .min(harvester.capacity_j - stored_j)
+            .max(0.0);
+
+        metrics.harvested_energy_j += match battery {
+            Some(battery) => battery.charge(nodes, id, energy_j),
+            None => {
+                nodes.set_remaining_energy_j(id, nodes.remaining_energy_j(id).max(0.0) + energy_j);
+                energy_j
+            }
+        };
+
+        let remaining_j = nodes.remaining_energy_j(id);
+        if !nodes.is_alive(id) && remaining_j > 0.0 && remaining_j >= harvester.revival_threshold_j {
+            nodes.set_alive(id, true);
+            *alive_node_count += 1;
+            metrics.revived_nodes += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn day_night_harvests_nothing_at_night() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let profiles = [
+            HarvestProfile::DayNight { peak_power_w: 1.0, day_length_s: 100.0 },
+            HarvestProfile::Cloudy {
+                peak_power_w: 1.0,
+                day_length_s: 100.0,
+                cloud_probability: 0.5,
+                cloud_transmission: 0.5,
+            },
+        ];
+
+        for profile in profiles {
+            let harvester = EnergyHarvester::new(profile);
+
+            // One-second rounds: rounds 1–50 fall in the first day, 51–100 in its night
+            for round in 1..=300 {
+                let energy_j = harvester.round_energy_j(round, 1.0, &mut rng);
+                let is_night = (round - 1) % 100 >= 50;
+
+                assert_eq!(is_night, harvester.predicted_energy_j(round, 1.0) == 0.0, "round {round}");
+                if is_night {
+                    assert_eq!(energy_j, 0.0, "round {round}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn day_night_peaks_at_solar_noon() {
+        let harvester = EnergyHarvester::new(HarvestProfile::DayNight { peak_power_w: 2.0, day_length_s: 100.0 });
+
+        // Round 25.5 would be exactly noon; rounds 25 and 26 straddle it
+        let noon_j = harvester.predicted_energy_j(26, 1.0);
+        assert!((noon_j - 2.0).abs() < 1e-3);
+        assert!((1..=100).all(|round| harvester.predicted_energy_j(round, 1.0) <= noon_j));
+    }
+
+    #[test]
+    fn harvest_stops_at_capacity() {
+        let harvester = EnergyHarvester::new(HarvestProfile::Constant { power_w: 1.0 }).with_capacity(1.0);
+        let mut simulator = Simulator::with_seed(100.0, 100.0, 10, 3).with_energy_harvesting(harvester);
+        for id in 0..10 {
+            // Half the nodes start below the capacity, half above it
+            let energy_j = if id % 2 == 0 { 0.25 } else { 1.5 };
+            simulator.edit_node(id, |node| node.remaining_energy_j = energy_j);
+        }
+
+        for _ in 0..3 {
+            harvest_round(&mut simulator);
+        }
+
+        for (id, node) in simulator.nodes().iter().enumerate() {
+            let expected_j = if id % 2 == 0 { 1.0 } else { 1.5 };
+            assert_eq!(node.remaining_energy_j, expected_j, "node {id}");
+        }
+        assert!((simulator.metrics.harvested_energy_j - 5.0 * 0.75).abs() < 1e-5);
+    }
+}
@@ -1,9 +1,18 @@
-
-use crate::config::DATA_PACKET_SIZE_BITS;
-use crate::simulator::{Protocol, Simulator};
+use crate::config::{DATA_PACKET_SIZE_BITS, INITIAL_NODE_ENERGY_J, MAC_CONTENTION_WINDOW_SLOTS};
+use crate::simulator::{active_store, Protocol, Simulator};
 use crate::utils::*;
-use rand::Rng;
-use crate::node::Node;
+use rand::{Rng, RngCore};
+use crate::clustering::ClusterQuality;
+use crate::columns::NodeStore;
+use crate::field::FieldSensing;
+use crate::handover::{collect_member_data, HandoverConfig};
+use crate::link::{report_isolated, send_to_sink, LinkLayer};
+use crate::frame::start_data_frame;
+use crate::mac::{advertise_cluster_heads, schedule_tdma, AdvertisementReception, TdmaFrame};
+use crate::metrics::RoundMetrics;
+use crate::power::DutyCycle;
+use crate::routing::InterClusterRouter;
+use crate::spatial::SpatialGrid;
 
 /// Implementation of the LEACH (Low-Energy Adaptive Clustering Hierarchy) protocol.
 ///
@@ -11,7 +20,18 @@ use crate::node::Node;
 /// - Cluster heads are selected probabilistically with rotation.
 /// - Non-CH nodes join the nearest CH and deduct transmission energy to it.
 /// - CHs deduct energy for receiving from members, aggregating data,
-///   and transmitting one aggregated packet to the base station.
+///   and transmitting one aggregated packet to the base station
+///   (directly, or over the inter-cluster routing layer when enabled).
+/// - Every data hop crosses the simulator's link layer and may be lost.
+/// - With the simulator's MAC layer enabled, CH advertisements contend via CSMA
+///   and member data is sent in TDMA slots, so both can collide.
+/// - With the simulator's control plane enabled, advertisements, join requests
+///   and TDMA schedules cost energy.
+/// - Clusters are formed once per round; members then send one packet in each of
+///   the simulator's steady-state frames.
+/// - With harvest-aware election, a node's election threshold scales with
+///   `(E + predicted harvest) / E_init`, clamped to [0, 1], where `E` is the energy
+///   it holds and the prediction covers this round.
 pub struct Leach {
     /// Current election threshold T(n) — updated each round
     election_threshold: f32,
@@ -21,6 +41,17 @@ pub struct Leach {
 
     /// Length of one full rotation cycle (1/p rounds on average)
     cycle_length_rounds: usize,
+
+    /// Optional CH-level multi-hop routing (`None` → every CH transmits directly to BS)
+    routing: Option<InterClusterRouter>,
+
+    /// Optional mid-round CH handover (`None` → a depleted CH finishes the round
+    /// and dies afterwards)
+    handover: Option<HandoverConfig>,
+
+    /// Whether the election threshold is weighted by residual plus predicted
+    /// harvested energy
+    harvest_aware_election: bool,
 }
 
 impl Leach {
@@ -30,9 +61,34 @@ impl Leach {
             election_threshold: 0.0, // will be updated in first round
             cluster_head_probability,
             cycle_length_rounds: (1.0 / cluster_head_probability) as usize,
+            routing: None,
+            handover: None,
+            harvest_aware_election: false,
         }
     }
 
+    /// Forwards aggregated data to the sink over the given inter-cluster router
+    /// instead of direct CH → BS transmissions.
+    pub fn with_routing(mut self, router: InterClusterRouter) -> Self {
+        self.routing = Some(router);
+        self
+    }
+
+    /// Enables mid-round handover: a CH dropping below the threshold passes its
+    /// role to the member with the most residual energy.
+    pub fn with_handover(mut self, handover: HandoverConfig) -> Self {
+        self.handover = Some(handover);
+        self
+    }
+
+    /// Scales every node's election threshold by `(E_residual + E_harvest) / E_initial`
+    /// (capped at 1), where `E_harvest` is the energy its harvester is expected to
+    /// collect this round.
+    pub fn with_harvest_aware_election(mut self) -> Self {
+        self.harvest_aware_election = true;
+        self
+    }
+
     /// Updates the cluster head election threshold T(n) for the current round
     /// according to the standard LEACH formula.
     fn update_election_threshold(&mut self, current_round: usize) {
@@ -48,99 +104,300 @@ impl Leach {
     ///
     /// This represents the phase where nodes send data to their CH
     /// (join cost is often considered negligible or merged here).
-    fn form_clusters(nodes: &mut Vec<Node>, cluster_head_ids: &Vec<usize>) {
-        for node_id in 0..nodes.len() {
-            
-            if nodes[node_id].is_alive && !nodes[node_id].is_cluster_head {
-                let mut min_distance_m = f32::INFINITY;
-                let mut nearest_ch_id: Option<usize> = None;
-
-                for &ch_id in cluster_head_ids {
-                    let distance = (nodes[node_id].position - nodes[ch_id].position).length();
-                    if distance < min_distance_m {
-                        min_distance_m = distance;
-                        nearest_ch_id = Some(ch_id);
+    /// - Without any CH this round, the readings of all other nodes are dropped
+    /// - A node whose nearest CH is out of radio range sends its reading straight
+    ///   to the sink, or is reported isolated if the sink is out of range too
+    /// - With `advertisements` (MAC enabled), a node joins the nearest CH it heard;
+    ///   a node whose advertisements all collided cannot join and its reading is dropped
+    ///
+    /// Returns the nodes that sent their reading straight to the sink.
+    fn form_clusters<S: NodeStore + ?Sized>(
+        nodes: &mut S,
+        cluster_head_ids: &[usize],
+        advertisements: Option<&[AdvertisementReception]>,
+        link: &LinkLayer,
+        rng: &mut dyn RngCore,
+        metrics: &mut RoundMetrics,
+        mut field: Option<&mut FieldSensing>,
+    ) -> Vec<usize> {
+        let cluster_head_grid = SpatialGrid::from_nodes(nodes, cluster_head_ids);
+        let mut direct_ids: Vec<usize> = Vec::new();
+
+        for node_id in 0..nodes.node_count() {
+            if nodes.is_alive(node_id) && !nodes.is_cluster_head(node_id) {
+                let nearest = match advertisements.map(|receptions| receptions[node_id]) {
+                    Some(AdvertisementReception::Heard { cluster_head_id, distance_m }) => {
+                        Some((cluster_head_id, distance_m))
                     }
-                }
+                    Some(AdvertisementReception::Collided) => {
+                        metrics.record_dropped_readings(&[node_id]);
+                        continue;
+                    }
+                    Some(AdvertisementReception::OutOfReach) | None => {
+                        cluster_head_grid.nearest(nodes.position(node_id))
+                    }
+                };
 
-                if let Some(ch_id) = nearest_ch_id {
-                    nodes[node_id].cluster_head_id = Some(ch_id);
-                    nodes[ch_id].cluster_member_ids.push(node_id);
-                    nodes[node_id].remaining_energy_j -=
-                        calculate_transmit_energy(DATA_PACKET_SIZE_BITS, min_distance_m);
+                match nearest {
+                    Some((ch_id, min_distance_m)) if link.in_range(nodes, node_id, min_distance_m) => {
+                        nodes.join_cluster(node_id, ch_id);
+                        nodes.consume_energy(
+                            node_id,
+                            calculate_transmit_energy(DATA_PACKET_SIZE_BITS, min_distance_m),
+                        );
+                        metrics.record_packets(node_id, |packets| packets.sent_to_cluster_head += 1);
+                    }
+                    Some(_) => {
+                        send_to_sink(nodes, node_id, &[&[node_id]], link, rng, metrics, field.as_deref_mut());
+                        direct_ids.push(node_id);
+                    }
+                    None => {
+                        let distance_to_bs = nodes.distance_to_base_station_m(node_id);
+                        if link.in_range(nodes, node_id, distance_to_bs) {
+                            metrics.record_dropped_readings(&[node_id]);
+                        } else {
+                            report_isolated(nodes, node_id, &[node_id], metrics);
+                        }
+                    }
                 }
             }
         }
-    }
-}
 
-impl Protocol for Leach {
-    fn name(&self) -> &'static str {
-        "LEACH"
+        nodes.finish_cluster_formation();
+        direct_ids
     }
 
-    /// Executes one full round of the LEACH protocol.
-    fn run_round(&mut self, simulator: &mut Simulator) {
-        self.update_election_threshold(simulator.current_round);
+    /// Runs one steady-state frame: CHs receive and aggregate member data (with
+    /// optional mid-round handover), then forward it to the sink.
+    ///
+    /// Returns the CHs that are still serving their cluster at the end of the frame.
+    fn run_data_frame(
+        &self,
+        simulator: &mut Simulator,
+        cluster_head_ids: &[usize],
+        tdma: Option<&TdmaFrame>,
+        evaluate_quality: bool,
+    ) -> Vec<usize> {
+        // Phase 3: Cluster head energy costs (receive + aggregate, with optional
+        // mid-round handover, then transmit to BS)
+        let mut forwarding_cluster_head_ids: Vec<usize> = Vec::new();
+        let mut reading_origin_ids: Vec<Vec<usize>> = Vec::new();
 
-        let mut rng = rand::rng();
-        let mut selected_cluster_head_ids: Vec<usize> = Vec::new();
+        for &ch_id in cluster_head_ids {
+            if !simulator.node_store().is_alive(ch_id) {
+                continue;
+            }
 
-        // Phase 1: Reset state, handle dead nodes, elect cluster heads
-        for node_id in 0..simulator.nodes.len() {
-            let node = &mut simulator.nodes[node_id];
+            // Receive + aggregate data from all members
+            let aggregate = collect_member_data(
+                simulator,
+                ch_id,
+                self.handover.as_ref(),
+                &|nodes: &dyn NodeStore, id| nodes.remaining_energy_j(id),
+                tdma,
+            );
+
+            if let Some((final_ch_id, origin_ids)) = aggregate {
+                forwarding_cluster_head_ids.push(final_ch_id);
+                reading_origin_ids.push(origin_ids);
+            }
+        }
+
+        let Simulator {
+            nodes,
+            columns,
+            distance_cache,
+            link,
+            rng,
+            metrics,
+            collect_cluster_quality,
+            field,
+            ..
+        } = simulator;
+        let nodes = active_store(nodes, columns);
+
+        if evaluate_quality && *collect_cluster_quality {
+            metrics.cluster_quality = ClusterQuality::from_cluster_heads(nodes);
+        }
 
-            reset_node_for_new_round(node);
+        // CHs that cannot reach the sink fall back to multi-hop routing over other CHs
+        let all_in_sink_range = forwarding_cluster_head_ids
+            .iter()
+            .all(|&ch_id| link.in_range(nodes, ch_id, nodes.distance_to_base_station_m(ch_id)));
 
+        let routes = match &self.routing {
+            // Phase 4 (optional): multi-hop forwarding of aggregated packets to the sink
+            Some(router) => Some(router.build_routes(
+                nodes,
+                &forwarding_cluster_head_ids,
+                distance_cache.as_ref(),
+                link,
+            )),
+            None if !all_in_sink_range => Some(InterClusterRouter::build_fallback_routes(
+                nodes,
+                &forwarding_cluster_head_ids,
+                distance_cache.as_ref(),
+                link,
+            )),
+            None => None,
+        };
+
+        match routes {
+            Some(routes) => routes.forward_to_sink(nodes, &reading_origin_ids, link, rng, metrics, field.as_mut()),
+            None => {
+                // Transmit one aggregated packet to the base station
+                for (&ch_id, origin_ids) in forwarding_cluster_head_ids.iter().zip(&reading_origin_ids) {
+                    send_to_sink(nodes, ch_id, &[origin_ids], link, rng, metrics, field.as_mut());
+                }
+            }
+        }
+
+        forwarding_cluster_head_ids
+    }
+
+    /// Resets the round state, restarts the rotation at the start of each cycle,
+    /// retires depleted nodes and elects this round's cluster heads.
+    ///
+    /// Returns the elected CH ids and the number of nodes that died.
+    fn elect_cluster_heads<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &mut S,
+        current_round: usize,
+        predicted_harvest_j: Option<f32>,
+        rng: &mut impl Rng,
+    ) -> (Vec<usize>, usize) {
+        let mut selected_cluster_head_ids: Vec<usize> = Vec::new();
+        let mut deaths = 0;
+
+        nodes.reset_round_state();
+
+        for node_id in 0..nodes.node_count() {
             // Reset eligibility at the start of each new cycle
-            if simulator.current_round % self.cycle_length_rounds == 0 {
-                node.is_eligible_for_ch = true;
+            if current_round.is_multiple_of(self.cycle_length_rounds) {
+                nodes.set_eligible_for_ch(node_id, true);
             }
 
             // Mark node dead if energy depleted
-            if node.is_alive && node.remaining_energy_j <= 0.0 {
-                node.is_alive = false;
-                simulator.alive_node_count -= 1;
+            if nodes.is_alive(node_id) && nodes.remaining_energy_j(node_id) <= 0.0 {
+                nodes.set_alive(node_id, false);
+                deaths += 1;
                 continue;
             }
 
             // Probabilistic cluster head election
-            if rng.random::<f32>() < self.election_threshold
-                && node.is_alive
-                && node.is_eligible_for_ch
+            let election_threshold = match predicted_harvest_j {
+                Some(harvest_j) => {
+                    let expected_energy_j =
+                        nodes.remaining_energy_j(node_id) + harvest_j * nodes.harvest_scale(node_id);
+                    self.election_threshold * (expected_energy_j / INITIAL_NODE_ENERGY_J).clamp(0.0, 1.0)
+                }
+                None => self.election_threshold,
+            };
+            if rng.random::<f32>() < election_threshold
+                && nodes.is_alive(node_id)
+                && nodes.is_eligible_for_ch(node_id)
             {
-                node.is_cluster_head = true;
-                node.is_eligible_for_ch = false;
+                nodes.promote_to_cluster_head(node_id, current_round);
                 selected_cluster_head_ids.push(node_id);
             }
         }
 
-        // Phase 2: Cluster assignment + member → CH data transmission energy
-        Leach::form_clusters(&mut simulator.nodes, &selected_cluster_head_ids);
+        (selected_cluster_head_ids, deaths)
+    }
+}
 
-        // Phase 3: Cluster head energy costs (receive + aggregate + transmit to BS)
-        for &ch_id in selected_cluster_head_ids.iter() {
-            let ch_node = &mut simulator.nodes[ch_id];
+impl Protocol for Leach {
+    fn name(&self) -> &'static str {
+        "LEACH"
+    }
 
-            if !ch_node.is_alive {
-                continue;
-            }
+    /// Every node listens through the advertisement contention window and the
+    /// schedule broadcast; members sleep outside their TDMA slot.
+    fn duty_cycle(&self, simulator: &Simulator) -> DutyCycle {
+        let contention_window_slots = simulator
+            .mac
+            .map_or(MAC_CONTENTION_WINDOW_SLOTS, |mac| mac.contention_window_slots);
 
-            let member_count = ch_node.cluster_member_ids.len() as f32;
+        DutyCycle {
+            setup_listen_slots: contention_window_slots + 1,
+            sleep_outside_slot: true,
+        }
+    }
 
-            // Receive + aggregate data from all members
-            ch_node.remaining_energy_j -=
-                (calculate_receive_energy(DATA_PACKET_SIZE_BITS)
-                    + calculate_aggregation_energy(DATA_PACKET_SIZE_BITS))
-                    * member_count;
-
-            // Transmit one aggregated packet to the base station
-            ch_node.remaining_energy_j -= calculate_transmit_energy(
-                DATA_PACKET_SIZE_BITS,
-                ch_node.distance_to_base_station_m,
-            );
+    /// Executes one full round of the LEACH protocol.
+    fn run_round(&mut self, simulator: &mut Simulator) {
+        self.update_election_threshold(simulator.current_round);
+
+        let predicted_harvest_j = self.harvest_aware_election.then(|| simulator.predicted_harvest_j());
+        let broadcast_radius_m = simulator.area_diagonal_m();
+        let Simulator {
+            nodes,
+            columns,
+            current_round,
+            alive_node_count,
+            rng,
+            metrics,
+            distance_cache,
+            link,
+            mac,
+            control,
+            field,
+            ..
+        } = simulator;
+        let nodes = active_store(nodes, columns);
+
+        // Phase 1: Reset state, handle dead nodes, elect cluster heads
+        let (selected_cluster_head_ids, deaths) =
+            self.elect_cluster_heads(nodes, *current_round, predicted_harvest_j, rng);
+        *alive_node_count -= deaths;
+        metrics.record_generated_readings(nodes);
+
+        // Phase 2 (optional): CSMA advertisements of the elected CHs
+        if let Some(control) = control
+            && mac.is_none()
+        {
+            control.charge_advertisements(nodes, &selected_cluster_head_ids, link, broadcast_radius_m, metrics);
         }
+        let advertisements = mac.map(|mac| {
+            advertise_cluster_heads(
+                nodes,
+                &selected_cluster_head_ids,
+                &mac,
+                link,
+                broadcast_radius_m,
+                rng,
+                metrics,
+            )
+        });
 
+        // Phase 2: Cluster assignment + member → CH data transmission energy
+        let direct_ids = Leach::form_clusters(
+            nodes,
+            &selected_cluster_head_ids,
+            advertisements.as_deref(),
+            link,
+            rng,
+            metrics,
+            field.as_mut(),
+        );
 
+        if let Some(control) = control {
+            control.charge_join_requests(nodes, &selected_cluster_head_ids, distance_cache.as_ref(), metrics);
+            if mac.is_none() {
+                control.charge_schedules(nodes, &selected_cluster_head_ids, distance_cache.as_ref(), metrics);
+            }
+        }
+
+        // TDMA schedules of the data phase (optional)
+        let tdma = mac.map(|mac| schedule_tdma(nodes, &selected_cluster_head_ids, distance_cache.as_ref(), &mac, metrics));
+
+        // Phases 3-4, once per steady-state frame with the clusters formed above
+        let mut cluster_head_ids = selected_cluster_head_ids;
+        for frame in 0..simulator.frames_per_round {
+            if frame > 0 {
+                start_data_frame(simulator, &direct_ids);
+            }
+            cluster_head_ids = self.run_data_frame(simulator, &cluster_head_ids, tdma.as_ref(), frame == 0);
+        }
     }
 }
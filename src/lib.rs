@@ -4,4 +4,22 @@ pub mod utils;
 pub mod leach;
 pub mod simulator;
 pub mod clustering;
+pub mod cluster_count;
 pub mod zcr;
+pub mod routing;
+pub mod scoring;
+pub mod metrics;
+pub mod handover;
+pub mod spatial;
+pub mod columns;
+pub mod distance;
+pub mod link;
+pub mod mac;
+pub mod control;
+pub mod power;
+pub mod battery;
+pub mod harvest;
+pub mod field;
+mod parallel;
+mod frame;
+pub mod batch;
@@ -0,0 +1,386 @@
+use crate::config::{
+    DATA_PACKET_SIZE_BITS,
+    LINK_HALF_RECEPTION_DISTANCE_M,
+    LINK_PATH_LOSS_EXPONENT,
+    LINK_SHADOWING_STD_DEV_DB,
+};
+use crate::columns::NodeStore;
+use crate::field::{charge_extra_payload, FieldSensing};
+use crate::metrics::RoundMetrics;
+use crate::utils::calculate_transmit_energy;
+use rand::{Rng, RngCore};
+
+/// Packet reception probability (PRR) of a single hop as a function of its length.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkModel {
+    /// Every packet arrives
+    Ideal,
+
+    /// Log-normal shadowing: the SNR margin at distance d is
+    /// `-10·n·log10(d / d50) + X`, X ~ N(0, σ²) dB, and a packet arrives when the
+    /// margin is positive, so `PRR(d) = Φ(-10·n·log10(d / d50) / σ)`.
+    LogNormalShadowing {
+        /// Path-loss exponent n
+        path_loss_exponent: f32,
+
+        /// Standard deviation σ of the shadowing term (dB)
+        shadowing_std_dev_db: f32,
+
+        /// Distance d50 at which half of the packets arrive (meters)
+        half_reception_distance_m: f32,
+    },
+
+    /// Piecewise-linear PRR curve through `(distance_m, prr)` points sorted by distance;
+    /// clamped to the first / last point outside their range
+    PrrCurve(Vec<(f32, f32)>),
+}
+
+impl LinkModel {
+    /// Log-normal shadowing with the default parameters from `config`.
+    pub fn log_normal_shadowing() -> Self {
+        LinkModel::LogNormalShadowing {
+            path_loss_exponent: LINK_PATH_LOSS_EXPONENT,
+            shadowing_std_dev_db: LINK_SHADOWING_STD_DEV_DB,
+            half_reception_distance_m: LINK_HALF_RECEPTION_DISTANCE_M,
+        }
+    }
+
+    /// Probability that one transmission over `distance_m` meters is received.
+    pub fn reception_probability(&self, distance_m: f32) -> f32 {
+        match self {
+            LinkModel::Ideal => 1.0,
+            LinkModel::LogNormalShadowing {
+                path_loss_exponent,
+                shadowing_std_dev_db,
+                half_reception_distance_m,
+            } => {
+                if distance_m <= 0.0 {
+                    return 1.0;
+                }
+                let margin_db = -10.0 * path_loss_exponent * (distance_m / half_reception_distance_m).log10();
+                if *shadowing_std_dev_db <= 0.0 {
+                    return if margin_db >= 0.0 { 1.0 } else { 0.0 };
+                }
+                standard_normal_cdf(margin_db / shadowing_std_dev_db)
+            }
+            LinkModel::PrrCurve(points) => interpolate_prr(points, distance_m),
+        }
+    }
+}
+
+/// Maximum transmission range of each radio class (`Node::radio_class`).
+///
+/// Classes without an explicit range use the default range (unlimited unless set).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadioRange {
+    /// Range of every class not listed in `class_ranges_m` (meters)
+    default_range_m: f32,
+
+    /// Range per class id (meters)
+    class_ranges_m: Vec<f32>,
+}
+
+impl Default for RadioRange {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl RadioRange {
+    /// Every node reaches every other node and the sink.
+    pub fn unlimited() -> Self {
+        Self::uniform(f32::INFINITY)
+    }
+
+    /// The same range for every class.
+    pub fn uniform(range_m: f32) -> Self {
+        Self {
+            default_range_m: range_m,
+            class_ranges_m: Vec::new(),
+        }
+    }
+
+    /// Sets the range of one radio class.
+    pub fn with_class_range(mut self, class: usize, range_m: f32) -> Self {
+        if self.class_ranges_m.len() <= class {
+            self.class_ranges_m.resize(class + 1, self.default_range_m);
+        }
+        self.class_ranges_m[class] = range_m;
+        self
+    }
+
+    /// Maximum transmission range of `class` (meters).
+    pub fn max_range_m(&self, class: usize) -> f32 {
+        self.class_ranges_m.get(class).copied().unwrap_or(self.default_range_m)
+    }
+
+    /// Whether a radio of `class` can transmit over `distance_m` meters.
+    pub fn reaches(&self, class: usize, distance_m: f32) -> bool {
+        distance_m <= self.max_range_m(class)
+    }
+}
+
+/// Outcome of sending one packet over a hop, including retransmissions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkOutcome {
+    /// Transmissions made (1 + retransmissions)
+    pub attempts: usize,
+
+    /// Whether one of the attempts was received
+    pub delivered: bool,
+}
+
+impl LinkOutcome {
+    /// Transmissions after the first one.
+    pub fn retransmissions(&self) -> usize {
+        self.attempts - 1
+    }
+}
+
+/// Link layer shared by all protocols: a reception model, optional ARQ and the
+/// maximum radio range.
+///
+/// - Without ARQ every packet is sent once and may be lost
+/// - With ARQ a lost packet is resent up to `max_retransmissions` times; the sender
+///   pays TX and the receiver RX for every attempt (acknowledgements are free)
+/// - Hops longer than the sender's radio range are never attempted; protocols
+///   look for another route or report the sender as isolated
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkLayer {
+    /// Reception probability of one transmission
+    model: LinkModel,
+
+    /// Retransmissions allowed per packet (0 → no ARQ)
+    max_retransmissions: usize,
+
+    /// Maximum transmission range per radio class
+    radio_range: RadioRange,
+}
+
+impl Default for LinkLayer {
+    fn default() -> Self {
+        Self::new(LinkModel::Ideal)
+    }
+}
+
+impl LinkLayer {
+    /// Creates a link layer without retransmissions.
+    pub fn new(model: LinkModel) -> Self {
+        Self {
+            model,
+            max_retransmissions: 0,
+            radio_range: RadioRange::unlimited(),
+        }
+    }
+
+    /// Enables ARQ with up to `max_retransmissions` retransmissions per packet.
+    pub fn with_arq(mut self, max_retransmissions: usize) -> Self {
+        self.max_retransmissions = max_retransmissions;
+        self
+    }
+
+    /// Limits how far each radio class can transmit.
+    pub fn with_radio_range(mut self, radio_range: RadioRange) -> Self {
+        self.radio_range = radio_range;
+        self
+    }
+
+    /// Maximum transmission range per radio class.
+    pub fn radio_range(&self) -> &RadioRange {
+        &self.radio_range
+    }
+
+    /// Whether node `id` of `nodes` can transmit over `distance_m` meters.
+    pub fn in_range<S: NodeStore + ?Sized>(&self, nodes: &S, id: usize, distance_m: f32) -> bool {
+        self.radio_range.reaches(nodes.radio_class(id), distance_m)
+    }
+
+    /// Reception model of a single transmission.
+    pub fn model(&self) -> &LinkModel {
+        &self.model
+    }
+
+    /// Sends one packet over `distance_m` meters.
+    ///
+    /// Draws from `rng` only when the hop can lose packets, so an ideal link
+    /// leaves the random sequence untouched.
+    pub fn transmit(&self, distance_m: f32, rng: &mut dyn RngCore) -> LinkOutcome {
+        let reception_probability = self.model.reception_probability(distance_m);
+
+        if reception_probability >= 1.0 {
+            return LinkOutcome { attempts: 1, delivered: true };
+        }
+
+        for attempt in 1..=self.max_retransmissions + 1 {
+            if rng.random::<f32>() < reception_probability {
+                return LinkOutcome { attempts: attempt, delivered: true };
+            }
+        }
+
+        LinkOutcome {
+            attempts: self.max_retransmissions + 1,
+            delivered: false,
+        }
+    }
+}
+
+/// Sends one data packet from node `id` straight to the base station over `link`.
+///
+/// - `aggregates` are the readings the packet carries, one entry per forwarding CH
+///   (its own first, then any it relays)
+/// - Charges TX for every attempt, plus the extra compressed payload of every
+///   aggregate with `field`, and records the readings as delivered or dropped
+/// - A node whose range does not reach the sink is reported as isolated instead
+///
+/// Returns whether the packet arrived.
+pub(crate) fn send_to_sink<S: NodeStore + ?Sized>(
+    nodes: &mut S,
+    id: usize,
+    aggregates: &[&[usize]],
+    link: &LinkLayer,
+    rng: &mut dyn RngCore,
+    metrics: &mut RoundMetrics,
+    mut field: Option<&mut FieldSensing>,
+) -> bool {
+    let distance_to_bs = nodes.distance_to_base_station_m(id);
+    if !link.in_range(nodes, id, distance_to_bs) {
+        for origin_ids in aggregates {
+            report_isolated(nodes, id, origin_ids, metrics);
+        }
+        return false;
+    }
+
+    let outcome = link.transmit(distance_to_bs, rng);
+    nodes.consume_energy(
+        id,
+        calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance_to_bs) * outcome.attempts as f32,
+    );
+    metrics.record_packets(id, |packets| packets.sent_to_base_station += 1);
+    metrics.record_link_outcome(id, outcome);
+
+    for origin_ids in aggregates {
+        if let Some(field) = field.as_deref_mut() {
+            let extra_bits = field.extra_payload_bits(origin_ids);
+            charge_extra_payload(nodes, id, None, distance_to_bs, outcome.attempts, extra_bits, metrics);
+            if outcome.delivered {
+                field.receive_aggregate(nodes, origin_ids);
+            }
+        }
+
+        if outcome.delivered {
+            metrics.record_delivered_readings(origin_ids);
+        } else {
+            metrics.record_dropped_readings(origin_ids);
+        }
+    }
+    outcome.delivered
+}
+
+/// Marks node `id` as isolated for this round and drops the readings it holds.
+///
+/// A node is counted in `disconnected_nodes` once per round, however many frames
+/// it fails to send.
+pub(crate) fn report_isolated<S: NodeStore + ?Sized>(
+    nodes: &mut S,
+    id: usize,
+    origin_ids: &[usize],
+    metrics: &mut RoundMetrics,
+) {
+    if !nodes.is_isolated(id) {
+        nodes.set_isolated(id, true);
+        metrics.disconnected_nodes += 1;
+    }
+    metrics.record_dropped_readings(origin_ids);
+}
+
+/// Linear interpolation of a `(distance_m, prr)` curve sorted by distance.
+fn interpolate_prr(points: &[(f32, f32)], distance_m: f32) -> f32 {
+    let (Some(&(first_distance, first_prr)), Some(&(_, last_prr))) = (points.first(), points.last()) else {
+        return 1.0;
+    };
+
+    if distance_m <= first_distance {
+        return first_prr;
+    }
+
+    for pair in points.windows(2) {
+        let ((d0, p0), (d1, p1)) = (pair[0], pair[1]);
+        if distance_m <= d1 {
+            if d1 <= d0 {
+                return p1;
+            }
+            return p0 + (p1 - p0) * (distance_m - d0) / (d1 - d0);
+        }
+    }
+
+    last_prr
+}
+
+/// Φ(x) of the standard normal distribution.
+///
+/// Uses the Abramowitz & Stegun 7.1.26 approximation of erf (absolute error < 1.5e-7).
+fn standard_normal_cdf(x: f32) -> f32 {
+    let z = x.abs() / std::f32::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * z);
+    let poly = t
+        * (0.254_829_6
+            + t * (-0.284_496_74 + t * (1.421_413_8 + t * (-1.453_152 + t * 1.061_405_4))));
+    let erf = 1.0 - poly * (-z * z).exp();
+
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_normal_reception_falls_with_distance() {
+        let model = LinkModel::log_normal_shadowing();
+        let LinkModel::LogNormalShadowing { half_reception_distance_m, .. } = model else {
+            unreachable!();
+        };
+
+        assert!((model.reception_probability(half_reception_distance_m) - 0.5).abs() < 1e-6);
+        assert_eq!(model.reception_probability(0.0), 1.0);
+
+        let prrs: Vec<f32> = (1..=400)
+            .map(|step| model.reception_probability(step as f32 * half_reception_distance_m / 100.0))
+            .collect();
+        assert!(prrs.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(prrs[0] > 0.99 && prrs[399] < 0.01);
+    }
+
+    #[test]
+    fn prr_curve_interpolates_and_clamps() {
+        let points = [(10.0, 0.9), (20.0, 0.5), (40.0, 0.1)];
+
+        assert_eq!(interpolate_prr(&points, 0.0), 0.9);
+        assert_eq!(interpolate_prr(&points, 10.0), 0.9);
+        assert!((interpolate_prr(&points, 15.0) - 0.7).abs() < 1e-6);
+        assert!((interpolate_prr(&points, 30.0) - 0.3).abs() < 1e-6);
+        assert!((interpolate_prr(&points, 40.0) - 0.1).abs() < 1e-6);
+        assert_eq!(interpolate_prr(&points, 1000.0), 0.1);
+        assert_eq!(interpolate_prr(&[], 5.0), 1.0);
+    }
+
+    #[test]
+    fn class_range_overrides_only_its_class() {
+        let range = RadioRange::uniform(90.0).with_class_range(2, 250.0).with_class_range(0, 40.0);
+
+        assert_eq!(range.max_range_m(0), 40.0);
+        assert_eq!(range.max_range_m(1), 90.0);
+        assert_eq!(range.max_range_m(2), 250.0);
+        assert_eq!(range.max_range_m(7), 90.0);
+
+        assert!(range.reaches(2, 250.0));
+        assert!(!range.reaches(2, 250.1));
+        assert!(range.reaches(5, 90.0));
+        assert!(!range.reaches(1, 90.1));
+        assert!(RadioRange::unlimited().reaches(3, 1.0e9));
+    }
+}
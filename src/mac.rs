@@ -0,0 +1,301 @@
+use crate::columns::NodeStore;
+use crate::control::broadcast_to_members;
+use crate::config::{
+    CONTROL_PACKET_SIZE_BITS,
+    MAC_CONTENTION_WINDOW_SLOTS,
+    MAC_INTERFERENCE_RANGE_M,
+};
+use crate::distance::DistanceCache;
+use crate::link::LinkLayer;
+use crate::metrics::RoundMetrics;
+use crate::spatial::SpatialGrid;
+use crate::utils::{calculate_receive_energy, calculate_transmit_energy};
+use rand::{Rng, RngCore};
+
+/// Settings of the optional MAC layer (CSMA setup phase, TDMA data phase).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacConfig {
+    /// Backoff slots a CH chooses from before sending its advertisement
+    pub contention_window_slots: usize,
+
+    /// Radius around a receiving CH within which a transmitter of another cluster
+    /// corrupts the packet received in the same TDMA slot (meters)
+    pub interference_range_m: f32,
+
+    /// Size of advertisement and schedule packets (bits)
+    pub control_packet_bits: f32,
+}
+
+impl Default for MacConfig {
+    fn default() -> Self {
+        Self {
+            contention_window_slots: MAC_CONTENTION_WINDOW_SLOTS,
+            interference_range_m: MAC_INTERFERENCE_RANGE_M,
+            control_packet_bits: CONTROL_PACKET_SIZE_BITS,
+        }
+    }
+}
+
+/// What a node made of this round's CH advertisements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdvertisementReception {
+    /// Nearest CH whose advertisement arrived intact
+    Heard { cluster_head_id: usize, distance_m: f32 },
+
+    /// Advertisements reached the node, but every one of them collided
+    Collided,
+
+    /// No advertisement reached the node (also CHs and dead nodes)
+    OutOfReach,
+}
+
+/// Inter-cluster collisions of one TDMA data phase.
+#[derive(Debug, Clone, Default)]
+pub struct TdmaFrame {
+    /// Whether each node's data packet was corrupted, by node id
+    collided: Vec<bool>,
+
+    /// Slots per frame (size of the largest cluster)
+    slot_count: usize,
+}
+
+impl TdmaFrame {
+    /// Whether the data packet of `member_id` collides at its CH.
+    pub fn is_collided(&self, member_id: usize) -> bool {
+        self.collided.get(member_id).copied().unwrap_or(false)
+    }
+
+    /// Number of slots in the frame.
+    pub fn slot_count(&self) -> usize {
+        self.slot_count
+    }
+}
+
+/// CSMA setup phase: every CH broadcasts an advertisement after a random backoff.
+///
+/// - An advertisement reaches every node within the CH's radio range, capped at
+///   `broadcast_radius_m`
+/// - Advertisements sent in the same backoff slot start together (carrier sensing
+///   cannot separate them) and collide at every node reached by more than one
+/// - CHs pay TX for their advertisement; every other alive node pays RX for each
+///   advertisement reaching it, collided or not
+///
+/// Returns the reception of every node, indexed by node id.
+pub fn advertise_cluster_heads<S: NodeStore + ?Sized>(
+    nodes: &mut S,
+    cluster_head_ids: &[usize],
+    config: &MacConfig,
+    link: &LinkLayer,
+    broadcast_radius_m: f32,
+    rng: &mut dyn RngCore,
+    metrics: &mut RoundMetrics,
+) -> Vec<AdvertisementReception> {
+    let mut receptions = vec![AdvertisementReception::OutOfReach; nodes.node_count()];
+    if cluster_head_ids.is_empty() {
+        return receptions;
+    }
+
+    let window = config.contention_window_slots.max(1);
+    let slots: Vec<usize> = cluster_head_ids.iter().map(|_| rng.random_range(0..window)).collect();
+    let radii: Vec<f32> = cluster_head_ids
+        .iter()
+        .map(|&ch_id| link.radio_range().max_range_m(nodes.radio_class(ch_id)).min(broadcast_radius_m))
+        .collect();
+    let max_radius = radii.iter().copied().fold(0.0, f32::max);
+
+    for (&ch_id, &radius) in cluster_head_ids.iter().zip(&radii) {
+        let energy = calculate_transmit_energy(config.control_packet_bits, radius);
+        nodes.consume_energy(ch_id, energy);
+        metrics.mac_energy_j += energy;
+    }
+
+    // CHs indexed by their position in `cluster_head_ids`
+    let cluster_head_grid = SpatialGrid::new(
+        cluster_head_ids
+            .iter()
+            .enumerate()
+            .map(|(i, &ch_id)| (i, nodes.position(ch_id))),
+    );
+    let receive_energy = calculate_receive_energy(config.control_packet_bits);
+    let mut senders_per_slot = vec![0usize; window];
+    let mut reaching: Vec<(usize, f32)> = Vec::new();
+
+    for (node_id, reception) in receptions.iter_mut().enumerate() {
+        if !nodes.is_alive(node_id) || nodes.is_cluster_head(node_id) {
+            continue;
+        }
+
+        reaching.clear();
+        cluster_head_grid.for_each_within_radius(nodes.position(node_id), max_radius, |i, distance| {
+            if distance <= radii[i] {
+                reaching.push((i, distance));
+            }
+        });
+        if reaching.is_empty() {
+            continue;
+        }
+
+        let energy = receive_energy * reaching.len() as f32;
+        nodes.consume_energy(node_id, energy);
+        metrics.mac_energy_j += energy;
+
+        for &(i, _) in &reaching {
+            senders_per_slot[slots[i]] += 1;
+        }
+
+        let heard = reaching
+            .iter()
+            .filter(|&&(i, _)| senders_per_slot[slots[i]] == 1)
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(cluster_head_ids[a.0].cmp(&cluster_head_ids[b.0])));
+        metrics.advertisement_collisions += reaching
+            .iter()
+            .filter(|&&(i, _)| senders_per_slot[slots[i]] > 1)
+            .count();
+
+        *reception = match heard {
+            Some(&(i, distance_m)) => AdvertisementReception::Heard {
+                cluster_head_id: cluster_head_ids[i],
+                distance_m,
+            },
+            None => AdvertisementReception::Collided,
+        };
+
+        for &(i, _) in &reaching {
+            senders_per_slot[slots[i]] = 0;
+        }
+    }
+
+    receptions
+}
+
+/// TDMA schedule setup: every CH gives each member one slot (in member-list order)
+/// and broadcasts the schedule to its farthest member.
+///
+/// - CHs pay TX for the schedule, members pay RX
+/// - A member's data packet collides if a member of another cluster sends in the same
+///   slot within `interference_range_m` of the receiving CH (clusters share one channel)
+///
+/// Call after cluster formation; collisions are judged against the CHs at that point.
+pub fn schedule_tdma<S: NodeStore + ?Sized>(
+    nodes: &mut S,
+    cluster_head_ids: &[usize],
+    distances: Option<&DistanceCache>,
+    config: &MacConfig,
+    metrics: &mut RoundMetrics,
+) -> TdmaFrame {
+    let mut frame = TdmaFrame {
+        collided: vec![false; nodes.node_count()],
+        slot_count: 0,
+    };
+
+    // (member id, CH id) of every transmission, grouped by slot
+    let mut transmissions_per_slot: Vec<Vec<(usize, usize)>> = Vec::new();
+
+    for &ch_id in cluster_head_ids {
+        let member_ids = nodes.cluster_member_ids(ch_id);
+        if member_ids.is_empty() {
+            continue;
+        }
+
+        for (slot, &member_id) in member_ids.iter().enumerate() {
+            if transmissions_per_slot.len() <= slot {
+                transmissions_per_slot.push(Vec::new());
+            }
+            transmissions_per_slot[slot].push((member_id, ch_id));
+        }
+        metrics.mac_energy_j += broadcast_to_members(nodes, distances, ch_id, config.control_packet_bits);
+    }
+
+    frame.slot_count = transmissions_per_slot.len();
+
+    for transmissions in &transmissions_per_slot {
+        let transmitter_grid = SpatialGrid::new(
+            transmissions
+                .iter()
+                .enumerate()
+                .map(|(i, &(member_id, _))| (i, nodes.position(member_id))),
+        );
+
+        for &(member_id, ch_id) in transmissions {
+            transmitter_grid.for_each_within_radius(nodes.position(ch_id), config.interference_range_m, |i, _| {
+                if transmissions[i].1 != ch_id {
+                    frame.collided[member_id] = true;
+                }
+            });
+        }
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::RadioRange;
+    use crate::node::Node;
+    use glam::Vec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// CHs 0 and 1, 20 m apart; node 2 between them, node 3 near CH 0 only,
+    /// node 4 out of reach and node 5 dead between the CHs.
+    fn two_cluster_heads() -> Vec<Node> {
+        let positions = [(0.0, 0.0), (20.0, 0.0), (10.0, 0.0), (-30.0, 0.0), (100.0, 0.0), (10.0, 1.0)];
+        let mut nodes: Vec<Node> = positions
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| Node::new(id, Vec2::new(x, y)))
+            .collect();
+        nodes[0].is_cluster_head = true;
+        nodes[1].is_cluster_head = true;
+        nodes[5].is_alive = false;
+        nodes
+    }
+
+    /// Advertises CHs 0 and 1 over 35 m, all in one backoff slot.
+    fn advertise(nodes: &mut Vec<Node>, link: &LinkLayer, metrics: &mut RoundMetrics) -> Vec<AdvertisementReception> {
+        let config = MacConfig {
+            contention_window_slots: 1,
+            ..MacConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        advertise_cluster_heads(nodes, &[0, 1], &config, link, 35.0, &mut rng, metrics)
+    }
+
+    #[test]
+    fn advertisements_in_one_slot_collide_at_shared_listeners() {
+        let mut nodes = two_cluster_heads();
+        let mut metrics = RoundMetrics::default();
+        let receptions = advertise(&mut nodes, &LinkLayer::default(), &mut metrics);
+
+        assert_eq!(receptions, [
+            AdvertisementReception::OutOfReach,
+            AdvertisementReception::OutOfReach,
+            AdvertisementReception::Collided,
+            AdvertisementReception::Heard { cluster_head_id: 0, distance_m: 30.0 },
+            AdvertisementReception::OutOfReach,
+            AdvertisementReception::OutOfReach,
+        ]);
+        assert_eq!(metrics.advertisement_collisions, 2);
+
+        // Collided advertisements still cost the listener RX
+        let receive_energy = calculate_receive_energy(MacConfig::default().control_packet_bits);
+        let initial_energy = Node::new(0, Vec2::ZERO).remaining_energy_j;
+        assert!((initial_energy - nodes[2].remaining_energy_j - 2.0 * receive_energy).abs() < receive_energy / 10.0);
+        assert!((initial_energy - nodes[3].remaining_energy_j - receive_energy).abs() < receive_energy / 10.0);
+        assert_eq!(nodes[4].remaining_energy_j, initial_energy);
+    }
+
+    #[test]
+    fn advertisements_in_one_slot_do_not_collide_beyond_radio_range() {
+        // CH 1 only reaches 5 m, so node 2 hears CH 0 alone
+        let mut nodes = two_cluster_heads();
+        nodes[1].radio_class = 1;
+        let link = LinkLayer::default().with_radio_range(RadioRange::unlimited().with_class_range(1, 5.0));
+        let mut metrics = RoundMetrics::default();
+        let receptions = advertise(&mut nodes, &link, &mut metrics);
+
+        assert_eq!(receptions[2], AdvertisementReception::Heard { cluster_head_id: 0, distance_m: 10.0 });
+        assert_eq!(metrics.advertisement_collisions, 0);
+    }
+}
@@ -8,6 +8,7 @@ use zcr_wsn::config::{
     TOTAL_SENSOR_NODES,
 };
 use zcr_wsn::leach::Leach;
+use zcr_wsn::metrics::RoundMetrics;
 use zcr_wsn::simulator::Simulator;
 use macroquad::prelude::*;
 
@@ -31,7 +32,8 @@ async fn main() {
         DEPLOYMENT_AREA_WIDTH_M,
         DEPLOYMENT_AREA_HEIGHT_M,
         TOTAL_SENSOR_NODES,
-    );
+    )
+    .with_cluster_quality();
 
     // Create LEACH protocol instance with desired CH probability
     let mut protocol = Leach::new(CLUSTER_HEAD_PROBABILITY);
@@ -39,6 +41,11 @@ async fn main() {
     // File creation 
     let file: File = std::fs::File::create("../leach.csv").unwrap();
     let mut writer: BufWriter<File> = BufWriter::new(file);
+
+    // Per-round protocol metrics
+    let metrics_file: File = std::fs::File::create("../leach_metrics.csv").unwrap();
+    let mut metrics_writer: BufWriter<File> = BufWriter::new(metrics_file);
+    writeln!(metrics_writer, "{}", RoundMetrics::csv_header()).unwrap();
     
     // Accumulator for fixed-time-step simulation loop
     let mut time_accumulator = 0.0;
@@ -53,9 +60,15 @@ async fn main() {
         // Catch up simulation with fixed timestep (multiple updates possible per frame)
         while time_accumulator >= fixed_timestep {
             simulator.update(&mut protocol);
-            for node in simulator.nodes.iter(){
+            for node in simulator.nodes().iter(){
                 writeln!(writer,"{},{},{}",simulator.current_round,simulator.alive_node_count,node.remaining_energy_j).unwrap();
             }
+            writeln!(
+                metrics_writer,
+                "{}",
+                simulator.metrics.to_csv_row(simulator.current_round, simulator.alive_node_count)
+            )
+            .unwrap();
 
             time_accumulator -= fixed_timestep;
         }
@@ -0,0 +1,274 @@
+use crate::clustering::ClusterQuality;
+use crate::columns::NodeStore;
+use crate::link::LinkOutcome;
+
+/// Data-plane packet counts.
+///
+/// - `generated`, `delivered` and `dropped` count sensor readings and are attributed
+///   to the node that sensed them (an aggregated packet carries many readings)
+/// - The other fields count packets and are attributed to the node that sent
+///   (`sent_*`) or merged (`aggregated`, `relayed`) them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacketCounters {
+    /// Readings sensed (one per alive node and round)
+    pub generated: usize,
+
+    /// Data packets sent to a CH (member readings and CH → CH hops)
+    pub sent_to_cluster_head: usize,
+
+    /// Packets merged into a CH's aggregate (member data and relayed aggregates)
+    pub aggregated: usize,
+
+    /// Aggregates of other CHs merged and forwarded towards the base station
+    pub relayed: usize,
+
+    /// Packets transmitted to the base station
+    pub sent_to_base_station: usize,
+
+    /// Readings that reached the base station
+    pub delivered: usize,
+
+    /// Readings lost on the way (their CH died or a hop failed)
+    pub dropped: usize,
+
+    /// Retransmissions made by ARQ
+    pub retransmissions: usize,
+
+    /// Packets lost on a hop after every allowed attempt
+    pub link_losses: usize,
+}
+
+impl PacketCounters {
+    /// Delivered / generated readings (0 when nothing was generated).
+    pub fn delivery_ratio(&self) -> f32 {
+        if self.generated == 0 {
+            return 0.0;
+        }
+        self.delivered as f32 / self.generated as f32
+    }
+}
+
+/// Per-round statistics collected by the protocols.
+///
+/// Reset by the simulator at the start of every round.
+#[derive(Debug, Clone, Default)]
+pub struct RoundMetrics {
+    /// Member data packets lost because their cluster head died mid-round
+    pub lost_packets: usize,
+
+    /// Number of mid-round cluster-head handovers
+    pub cluster_head_handovers: usize,
+
+    /// Quality of the cluster structure formed this round
+    pub cluster_quality: ClusterQuality,
+
+    /// Alive nodes that could reach neither a CH nor the sink (marked `is_isolated`)
+    pub disconnected_nodes: usize,
+
+    /// Advertisements that collided at a listener (counted once per listener)
+    pub advertisement_collisions: usize,
+
+    /// Member data packets corrupted by another cluster's TDMA slot
+    pub data_collisions: usize,
+
+    /// Energy spent on MAC control traffic: advertisements and TDMA schedules (Joules)
+    pub mac_energy_j: f32,
+
+    /// Cluster-setup control messages sent (advertisements, joins, schedules,
+    /// status reports, assignments), excluding those charged by the MAC layer
+    pub control_packets: usize,
+
+    /// Energy spent on those control messages (Joules)
+    pub control_energy_j: f32,
+
+    /// Energy drawn by the power model: idle listening, sleep, sensing, MCU and any
+    /// extra TX / RX draw (Joules)
+    pub state_energy_j: f32,
+
+    /// Nodes the battery model declared depleted although the linear energy
+    /// counter still showed charge (rate-capacity loss or voltage cutoff)
+    pub battery_cutoffs: usize,
+
+    /// Energy stored by the nodes' harvesters this round (Joules)
+    pub harvested_energy_j: f32,
+
+    /// Dead nodes that recharged past the revival threshold this round
+    pub revived_nodes: usize,
+
+    /// Root-mean-square error of the sink's field reconstruction after this round
+    /// (0 without field sensing)
+    pub field_rmse: f32,
+
+    /// Energy spent sending and receiving compressed payloads beyond one data packet (Joules)
+    pub compression_energy_j: f32,
+
+    /// Packet counts of the whole network this round
+    pub packets: PacketCounters,
+
+    /// Packet counts per node id this round (nodes past the end recorded nothing)
+    pub node_packets: Vec<PacketCounters>,
+}
+
+impl RoundMetrics {
+    /// Applies `update` to the round totals and to the counters of `node_id`.
+    pub fn record_packets(&mut self, node_id: usize, update: impl Fn(&mut PacketCounters)) {
+        if self.node_packets.len() <= node_id {
+            self.node_packets.resize(node_id + 1, PacketCounters::default());
+        }
+        update(&mut self.packets);
+        update(&mut self.node_packets[node_id]);
+    }
+
+    /// Counts the retransmissions and a possible loss of one packet sent by `sender_id`.
+    pub fn record_link_outcome(&mut self, sender_id: usize, outcome: LinkOutcome) {
+        self.record_packets(sender_id, |packets| {
+            packets.retransmissions += outcome.retransmissions();
+            if !outcome.delivered {
+                packets.link_losses += 1;
+            }
+        });
+    }
+
+    /// Counts one generated reading for every alive node.
+    pub fn record_generated_readings<S: NodeStore + ?Sized>(&mut self, nodes: &S) {
+        for id in 0..nodes.node_count() {
+            if nodes.is_alive(id) {
+                self.record_packets(id, |packets| packets.generated += 1);
+            }
+        }
+    }
+
+    /// Counts the readings sensed by `origin_ids` as delivered to the base station.
+    pub fn record_delivered_readings(&mut self, origin_ids: &[usize]) {
+        for &id in origin_ids {
+            self.record_packets(id, |packets| packets.delivered += 1);
+        }
+    }
+
+    /// Counts the readings sensed by `origin_ids` as dropped.
+    pub fn record_dropped_readings(&mut self, origin_ids: &[usize]) {
+        for &id in origin_ids {
+            self.record_packets(id, |packets| packets.dropped += 1);
+        }
+    }
+
+    /// Column names matching `to_csv_row`.
+    pub fn csv_header() -> &'static str {
+        "round,alive_nodes,lost_packets,cluster_head_handovers,disconnected_nodes,\
+cluster_count,sse,silhouette,size_balance_index,\
+mean_member_distance_m,max_member_distance_m,empty_clusters,\
+generated,sent_to_cluster_head,aggregated,relayed,sent_to_base_station,\
+delivered,dropped,retransmissions,link_losses,delivery_ratio,\
+advertisement_collisions,data_collisions,mac_energy_j,control_packets,control_energy_j,state_energy_j,battery_cutoffs,\
+harvested_energy_j,revived_nodes,field_rmse,compression_energy_j"
+    }
+
+    /// One CSV row with the metrics of `round`.
+    pub fn to_csv_row(&self, round: usize, alive_nodes: usize) -> String {
+        let quality = &self.cluster_quality;
+        let packets = &self.packets;
+
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            round,
+            alive_nodes,
+            self.lost_packets,
+            self.cluster_head_handovers,
+            self.disconnected_nodes,
+            quality.cluster_count,
+            quality.sse,
+            quality.silhouette,
+            quality.size_balance_index,
+            quality.mean_member_distance_m,
+            quality.max_member_distance_m,
+            quality.empty_clusters,
+            packets.generated,
+            packets.sent_to_cluster_head,
+            packets.aggregated,
+            packets.relayed,
+            packets.sent_to_base_station,
+            packets.delivered,
+            packets.dropped,
+            packets.retransmissions,
+            packets.link_losses,
+            packets.delivery_ratio(),
+            self.advertisement_collisions,
+            self.data_collisions,
+            self.mac_energy_j,
+            self.control_packets,
+            self.control_energy_j,
+            self.state_energy_j,
+            self.battery_cutoffs,
+            self.harvested_energy_j,
+            self.revived_nodes,
+            self.field_rmse,
+            self.compression_energy_j,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leach::Leach;
+    use crate::link::{LinkLayer, LinkModel};
+    use crate::simulator::Simulator;
+    use crate::zcr::Zcr;
+
+    #[test]
+    fn node_counters_add_up_to_the_round_totals() {
+        let mut metrics = RoundMetrics::default();
+        metrics.record_delivered_readings(&[4, 1, 4]);
+        metrics.record_dropped_readings(&[2]);
+        metrics.record_link_outcome(1, LinkOutcome { attempts: 3, delivered: false });
+        metrics.record_link_outcome(2, LinkOutcome { attempts: 1, delivered: true });
+
+        assert_eq!(metrics.node_packets.len(), 5);
+        assert_eq!(metrics.node_packets[4].delivered, 2);
+        assert_eq!(metrics.node_packets[1].retransmissions, 2);
+        assert_eq!(metrics.node_packets[1].link_losses, 1);
+        assert_eq!(metrics.node_packets[2], PacketCounters { dropped: 1, ..PacketCounters::default() });
+
+        let total = metrics.node_packets.iter().fold(PacketCounters::default(), |sum, node| PacketCounters {
+            delivered: sum.delivered + node.delivered,
+            dropped: sum.dropped + node.dropped,
+            retransmissions: sum.retransmissions + node.retransmissions,
+            link_losses: sum.link_losses + node.link_losses,
+            ..sum
+        });
+        assert_eq!(total, metrics.packets);
+    }
+
+    #[test]
+    fn delivery_ratio_of_an_empty_round_is_zero() {
+        assert_eq!(PacketCounters::default().delivery_ratio(), 0.0);
+
+        let packets = PacketCounters { generated: 8, delivered: 6, ..PacketCounters::default() };
+        assert_eq!(packets.delivery_ratio(), 0.75);
+    }
+
+    #[test]
+    fn every_reading_is_delivered_or_dropped() {
+        let link = LinkLayer::new(LinkModel::log_normal_shadowing()).with_arq(1);
+        let mut leach_simulator = Simulator::with_seed(300.0, 300.0, 80, 12).with_link_layer(link.clone());
+        let mut zcr_simulator = Simulator::with_seed(300.0, 300.0, 80, 12).with_link_layer(link);
+        let (mut leach, mut zcr) = (Leach::new(0.1), Zcr::new(0.1));
+
+        for round in 1..=100 {
+            leach_simulator.update(&mut leach);
+            zcr_simulator.update(&mut zcr);
+
+            for packets in [&leach_simulator.metrics.packets, &zcr_simulator.metrics.packets] {
+                assert_eq!(packets.generated, packets.delivered + packets.dropped, "round {round}");
+                // No node dies this early, so only the link drops readings
+                assert!(packets.link_losses > 0 || packets.dropped == 0, "round {round}");
+            }
+        }
+    }
+
+    #[test]
+    fn csv_row_matches_the_header() {
+        let columns = RoundMetrics::csv_header().split(',').count();
+        assert_eq!(RoundMetrics::default().to_csv_row(1, 10).split(',').count(), columns);
+    }
+}
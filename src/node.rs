@@ -38,6 +38,20 @@ pub struct Node {
 
     /// List of member node IDs (only meaningful/used when this node is a Cluster Head)
     pub cluster_member_ids: Vec<usize>,
+
+    /// Round in which this node last served as a Cluster Head (`None` → never)
+    pub last_cluster_head_round: Option<usize>,
+
+    /// Radio hardware class, selects the maximum TX range (`link::RadioRange`)
+    pub radio_class: usize,
+
+    /// Whether this node could reach neither a cluster head nor the sink this round
+    pub is_isolated: bool,
+
+    /// Size of the node's energy harvester relative to the harvesting profile
+    /// (`harvest::EnergyHarvester`; 0 → no harvester); set per node with
+    /// `Simulator::with_harvest_scales`
+    pub harvest_scale: f32,
 }
 
 impl Node {
@@ -52,6 +66,7 @@ impl Node {
     /// - Node starts alive
     /// - Starts as non-Cluster Head
     /// - Eligible to become CH in round 1
+    /// - Radio class 0, not isolated, full-size energy harvester
     /// - Distance to base station is precomputed once
     pub fn new(id: usize, position: Vec2) -> Self {
         // Precompute distance (length is sqrt(x² + y²))
@@ -68,6 +83,10 @@ impl Node {
             distance_to_base_station_m,
             cluster_head_id: None,
             cluster_member_ids: Vec::new(),
+            last_cluster_head_round: None,
+            radio_class: 0,
+            is_isolated: false,
+            harvest_scale: 1.0,
         }
     }
 
@@ -80,9 +99,7 @@ impl Node {
     ///
     /// # Returns
     /// Vector of `Node`s with random positions inside (1..width, 1..height)
-    pub fn create_wsn(width: f32, height: f32, n_nodes: usize) -> Vec<Node> {
-        let mut rng = rand::rng();
-
+    pub fn create_wsn(width: f32, height: f32, n_nodes: usize, rng: &mut impl Rng) -> Vec<Node> {
         (0..n_nodes)
             .map(|id| {
                 let x = rng.random_range(1.0..width);
@@ -0,0 +1,61 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Number of items per chunk in `map_chunks` (fixed so reductions do not
+/// depend on the number of threads)
+pub(crate) const CHUNK_SIZE: usize = 4096;
+
+/// Applies `f` to every item, keeping the input order.
+///
+/// Runs on the rayon thread pool with the `parallel` feature, sequentially without it.
+pub(crate) fn map<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Applies `f` to consecutive chunks of `CHUNK_SIZE` items, keeping the chunk order.
+///
+/// Reducing the per-chunk results sequentially gives bit-identical output with and
+/// without the `parallel` feature, whatever the number of threads.
+pub(crate) fn map_chunks<T, R>(items: &[T], f: impl Fn(&[T]) -> R + Sync + Send) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.par_chunks(CHUNK_SIZE).map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.chunks(CHUNK_SIZE).map(f).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_keeps_the_input_order() {
+        let items: Vec<usize> = (0..10_000).collect();
+        assert_eq!(map(&items, |&item| item * 2), items.iter().map(|&item| item * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn map_chunks_uses_fixed_chunks() {
+        let items: Vec<usize> = (0..2 * CHUNK_SIZE + 5).collect();
+        let chunks = map_chunks(&items, |chunk| (chunk[0], chunk.len()));
+        assert_eq!(chunks, [(0, CHUNK_SIZE), (CHUNK_SIZE, CHUNK_SIZE), (2 * CHUNK_SIZE, 5)]);
+    }
+}
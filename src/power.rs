@@ -0,0 +1,314 @@
+use crate::columns::NodeStore;
+use crate::config::{
+    CONTROL_PACKET_SIZE_BITS,
+    DATA_PACKET_SIZE_BITS,
+    POWER_IDLE_W,
+    POWER_MCU_W,
+    POWER_RX_W,
+    POWER_SENSE_W,
+    POWER_SLEEP_W,
+    POWER_TX_W,
+    RADIO_DATA_RATE_BPS,
+    ROUND_DURATION_S,
+    SENSING_TIME_S,
+};
+use crate::metrics::RoundMetrics;
+
+/// How a protocol keeps its radios on during a round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DutyCycle {
+    /// Control-packet airtimes every node listens for during cluster setup
+    pub setup_listen_slots: usize,
+
+    /// Whether members switch their radio off outside their own TDMA slot
+    /// (`false` → the radio idles whenever it is not sending or receiving)
+    pub sleep_outside_slot: bool,
+}
+
+impl DutyCycle {
+    /// Radio never sleeps.
+    pub fn always_on() -> Self {
+        Self {
+            setup_listen_slots: 0,
+            sleep_outside_slot: false,
+        }
+    }
+}
+
+/// Time one node spent in each state during a round (seconds).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StateTimes {
+    /// Radio transmitting
+    pub tx_s: f32,
+
+    /// Radio receiving
+    pub rx_s: f32,
+
+    /// Radio listening to an idle channel
+    pub idle_s: f32,
+
+    /// Radio asleep
+    pub sleep_s: f32,
+
+    /// Sensor sampling
+    pub sense_s: f32,
+
+    /// MCU awake (radio awake or sensing)
+    pub mcu_s: f32,
+}
+
+/// Per-state power draws of a node, charged for the time it spends in each state.
+///
+/// - Packets are still charged per bit by the first-order radio model; `tx_power_w`
+///   and `rx_power_w` only add draw on top of it during TX / RX airtime
+/// - State times follow the cluster structure at the end of the round and the
+///   protocol's `DutyCycle`: every node sends one packet per frame, a CH listens to
+///   the whole TDMA frame, members sleep outside their slot if the protocol allows
+/// - Every alive node takes one reading per frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerModel {
+    /// Extra draw while transmitting (W)
+    pub tx_power_w: f32,
+
+    /// Extra draw while receiving (W)
+    pub rx_power_w: f32,
+
+    /// Draw while listening to an idle channel (W)
+    pub idle_power_w: f32,
+
+    /// Draw while radio and MCU sleep (W)
+    pub sleep_power_w: f32,
+
+    /// Draw of the sensor while sampling (W)
+    pub sense_power_w: f32,
+
+    /// Draw of the MCU while awake (W)
+    pub mcu_power_w: f32,
+
+    /// Length of one round (seconds)
+    pub round_duration_s: f32,
+
+    /// Radio bit rate (bits/s)
+    pub data_rate_bps: f32,
+
+    /// Time needed for one reading (seconds)
+    pub sensing_time_s: f32,
+}
+
+impl Default for PowerModel {
+    fn default() -> Self {
+        Self {
+            tx_power_w: POWER_TX_W,
+            rx_power_w: POWER_RX_W,
+            idle_power_w: POWER_IDLE_W,
+            sleep_power_w: POWER_SLEEP_W,
+            sense_power_w: POWER_SENSE_W,
+            mcu_power_w: POWER_MCU_W,
+            round_duration_s: ROUND_DURATION_S,
+            data_rate_bps: RADIO_DATA_RATE_BPS,
+            sensing_time_s: SENSING_TIME_S,
+        }
+    }
+}
+
+impl PowerModel {
+    /// Energy drawn over `times` (Joules).
+    pub fn energy_j(&self, times: &StateTimes) -> f32 {
+        times.tx_s * self.tx_power_w
+            + times.rx_s * self.rx_power_w
+            + times.idle_s * self.idle_power_w
+            + times.sleep_s * self.sleep_power_w
+            + times.sense_s * self.sense_power_w
+            + times.mcu_s * self.mcu_power_w
+    }
+
+    /// Time node `id` spent in each state during a round of `frames` steady-state
+    /// frames of `slots_per_frame` data slots each.
+    pub fn state_times<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &S,
+        id: usize,
+        duty_cycle: &DutyCycle,
+        frames: usize,
+        slots_per_frame: usize,
+    ) -> StateTimes {
+        let slot_s = DATA_PACKET_SIZE_BITS / self.data_rate_bps;
+        let frames = frames as f32;
+        let frame_s = slots_per_frame as f32 * slot_s;
+
+        let tx_s = frames * slot_s;
+        let rx_s = if nodes.is_cluster_head(id) {
+            nodes.cluster_member_ids(id).len() as f32 * frames * slot_s
+        } else {
+            0.0
+        };
+        let busy_s = tx_s + rx_s;
+
+        let idle_s = if !duty_cycle.sleep_outside_slot {
+            self.round_duration_s - busy_s
+        } else {
+            let setup_s = duty_cycle.setup_listen_slots as f32 * CONTROL_PACKET_SIZE_BITS / self.data_rate_bps;
+            let frame_listen_s = if nodes.is_cluster_head(id) { frames * frame_s - busy_s } else { 0.0 };
+            setup_s + frame_listen_s
+        }
+        .max(0.0);
+
+        let sleep_s = (self.round_duration_s - busy_s - idle_s).max(0.0);
+        let sense_s = frames * self.sensing_time_s;
+
+        StateTimes {
+            tx_s,
+            rx_s,
+            idle_s,
+            sleep_s,
+            sense_s,
+            mcu_s: (busy_s + idle_s + sense_s).min(self.round_duration_s),
+        }
+    }
+
+    /// Charges every alive node for the time it spent in each state this round.
+    pub fn charge_round<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &mut S,
+        duty_cycle: &DutyCycle,
+        frames: usize,
+        metrics: &mut RoundMetrics,
+    ) {
+        let slots_per_frame = tdma_slots_per_frame(nodes);
+
+        for id in 0..nodes.node_count() {
+            if !nodes.is_alive(id) {
+                continue;
+            }
+            let energy = self.energy_j(&self.state_times(nodes, id, duty_cycle, frames, slots_per_frame));
+            nodes.consume_energy(id, energy);
+            metrics.state_energy_j += energy;
+        }
+    }
+}
+
+/// Data slots in one TDMA frame: one per member of the largest cluster, plus the
+/// CH → sink transmission.
+pub fn tdma_slots_per_frame<S: NodeStore + ?Sized>(nodes: &S) -> usize {
+    (0..nodes.node_count())
+        .filter(|&ch_id| nodes.is_alive(ch_id) && nodes.is_cluster_head(ch_id))
+        .map(|ch_id| nodes.cluster_member_ids(ch_id).len())
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+    use glam::Vec2;
+
+    /// CH 0 with members 1–3, CH 4 with member 5, node 6 dead.
+    fn clustered_nodes() -> Vec<Node> {
+        let mut nodes: Vec<Node> = (0..7).map(|id| Node::new(id, Vec2::new(id as f32 * 10.0, 0.0))).collect();
+        nodes.promote_to_cluster_head(0, 1);
+        nodes.promote_to_cluster_head(4, 1);
+        for (member_id, ch_id) in [(1, 0), (2, 0), (3, 0), (5, 4)] {
+            nodes.join_cluster(member_id, ch_id);
+        }
+        nodes.finish_cluster_formation();
+        nodes[6].is_alive = false;
+        nodes
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "{actual} vs {expected}");
+    }
+
+    /// Asserts that the radio states of `times` fill the round exactly.
+    fn assert_fills_round(times: &StateTimes, round_duration_s: f32) {
+        assert_close(times.tx_s + times.rx_s + times.idle_s + times.sleep_s, round_duration_s);
+    }
+
+    #[test]
+    fn frame_has_one_slot_per_member_of_the_largest_cluster() {
+        assert_eq!(tdma_slots_per_frame(&clustered_nodes()), 4);
+
+        let unclustered: Vec<Node> = (0..3).map(|id| Node::new(id, Vec2::ZERO)).collect();
+        assert_eq!(tdma_slots_per_frame(&unclustered), 1);
+    }
+
+    #[test]
+    fn always_on_radio_idles_between_packets() {
+        let power = PowerModel::default();
+        let nodes = clustered_nodes();
+        let slot_s = DATA_PACKET_SIZE_BITS / power.data_rate_bps;
+
+        let member = power.state_times(&nodes, 1, &DutyCycle::always_on(), 2, 4);
+        assert_close(member.tx_s, 2.0 * slot_s);
+        assert_eq!(member.rx_s, 0.0);
+        assert_eq!(member.sleep_s, 0.0);
+        assert_close(member.sense_s, 2.0 * power.sensing_time_s);
+        assert_close(member.mcu_s, power.round_duration_s);
+        assert_fills_round(&member, power.round_duration_s);
+
+        // A CH receives one slot per member and frame
+        let cluster_head = power.state_times(&nodes, 0, &DutyCycle::always_on(), 2, 4);
+        assert_close(cluster_head.rx_s, 3.0 * 2.0 * slot_s);
+        assert_fills_round(&cluster_head, power.round_duration_s);
+    }
+
+    #[test]
+    fn duty_cycled_members_sleep_outside_their_slot() {
+        let power = PowerModel::default();
+        let nodes = clustered_nodes();
+        let slot_s = DATA_PACKET_SIZE_BITS / power.data_rate_bps;
+        let duty_cycle = DutyCycle {
+            setup_listen_slots: 5,
+            sleep_outside_slot: true,
+        };
+        let setup_s = 5.0 * CONTROL_PACKET_SIZE_BITS / power.data_rate_bps;
+
+        let member = power.state_times(&nodes, 5, &duty_cycle, 2, 4);
+        assert_close(member.idle_s, setup_s);
+        assert!(member.sleep_s > 0.9 * power.round_duration_s);
+        assert_fills_round(&member, power.round_duration_s);
+
+        // CH 4 idles through both 4-slot frames except while it sends or receives
+        let cluster_head = power.state_times(&nodes, 4, &duty_cycle, 2, 4);
+        assert_close(cluster_head.idle_s, setup_s + 2.0 * 4.0 * slot_s - 2.0 * 2.0 * slot_s);
+        assert_fills_round(&cluster_head, power.round_duration_s);
+
+        assert!(power.energy_j(&member) < power.energy_j(&power.state_times(&nodes, 5, &DutyCycle::always_on(), 2, 4)));
+    }
+
+    #[test]
+    fn energy_is_the_sum_of_state_draws() {
+        let power = PowerModel::default();
+        let times = StateTimes {
+            tx_s: 1.0,
+            rx_s: 2.0,
+            idle_s: 3.0,
+            sleep_s: 4.0,
+            sense_s: 5.0,
+            mcu_s: 6.0,
+        };
+        let expected = power.tx_power_w
+            + 2.0 * power.rx_power_w
+            + 3.0 * power.idle_power_w
+            + 4.0 * power.sleep_power_w
+            + 5.0 * power.sense_power_w
+            + 6.0 * power.mcu_power_w;
+        assert!((power.energy_j(&times) - expected).abs() <= expected * 1e-6);
+    }
+
+    #[test]
+    fn round_charge_skips_dead_nodes() {
+        let power = PowerModel::default();
+        let mut nodes = clustered_nodes();
+        let mut metrics = RoundMetrics::default();
+        power.charge_round(&mut nodes, &DutyCycle::always_on(), 1, &mut metrics);
+
+        let initial_energy_j = Node::new(0, Vec2::ZERO).remaining_energy_j;
+        let spent: Vec<f32> = nodes.iter().map(|node| initial_energy_j - node.remaining_energy_j).collect();
+        assert!(spent[..6].iter().all(|&energy| energy > 0.0));
+        assert_eq!(spent[6], 0.0);
+        assert!((metrics.state_energy_j - spent.iter().sum::<f32>()).abs() <= metrics.state_energy_j * 1e-3);
+    }
+}
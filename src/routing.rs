@@ -0,0 +1,556 @@
+use crate::columns::NodeStore;
+use crate::config::{DATA_PACKET_SIZE_BITS, INITIAL_NODE_ENERGY_J};
+use crate::distance::{node_distance, DistanceCache};
+use crate::field::{charge_extra_payload, FieldSensing};
+use crate::link::{report_isolated, LinkLayer};
+use crate::metrics::RoundMetrics;
+use crate::utils::{
+    calculate_aggregation_energy,
+    calculate_receive_energy,
+    calculate_transmit_energy,
+};
+use rand::RngCore;
+use std::collections::HashMap;
+
+/// Lower bound on the normalized residual energy used by `LinkCost::ResidualEnergyWeighted`
+/// (keeps the cost finite for nearly depleted receivers).
+const MIN_RESIDUAL_RATIO: f32 = 1e-3;
+
+/// Cost model for a single link in the cluster-head graph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkCost {
+    /// Sender TX energy plus receiver RX + aggregation energy (Joules).
+    TransmitEnergy,
+
+    /// `TransmitEnergy` divided by the receiver's normalized residual energy,
+    /// so routes avoid depleted cluster heads.
+    ResidualEnergyWeighted,
+
+    /// Every link costs 1 — minimum-hop routing.
+    HopCount,
+}
+
+/// Shortest-path algorithm used to build the routing table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShortestPath {
+    Dijkstra,
+    BellmanFord,
+}
+
+/// Next hop of a cluster head on its way to the sink.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NextHop {
+    /// Forward to another cluster head (node id)
+    ClusterHead(usize),
+
+    /// Transmit directly to the base station
+    Sink,
+
+    /// No in-range path to the base station exists
+    Unreachable,
+}
+
+/// Inter-cluster routing layer: builds a graph over the current cluster heads
+/// (plus the base station) and computes least-cost paths to the sink.
+#[derive(Debug, Clone, Copy)]
+pub struct InterClusterRouter {
+    /// Cost assigned to each CH → CH / CH → sink link
+    link_cost: LinkCost,
+
+    /// Algorithm used to solve single-destination shortest paths
+    algorithm: ShortestPath,
+}
+
+/// Result of routing one round: next hop and path cost for each cluster head.
+#[derive(Debug, Clone)]
+pub struct RoutingTable {
+    /// Cluster head node ids (vertex i of the graph is `cluster_head_ids[i]`)
+    cluster_head_ids: Vec<usize>,
+
+    /// Next hop for each cluster head (same order as `cluster_head_ids`)
+    next_hops: Vec<NextHop>,
+
+    /// Total path cost to the sink for each cluster head
+    path_costs: Vec<f32>,
+
+    /// Length of each cluster head's next hop (meters; 0 when unreachable)
+    hop_distances: Vec<f32>,
+}
+
+impl InterClusterRouter {
+    /// Creates a router with the given link cost model and shortest-path algorithm.
+    pub fn new(link_cost: LinkCost, algorithm: ShortestPath) -> Self {
+        Self {
+            link_cost,
+            algorithm,
+        }
+    }
+
+    /// Cost of sending one aggregated packet over `distance` meters to a receiver
+    /// holding `receiver_energy_j`.
+    fn cluster_head_link_cost(&self, distance: f32, receiver_energy_j: f32) -> f32 {
+        let energy = calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance)
+            + calculate_receive_energy(DATA_PACKET_SIZE_BITS)
+            + calculate_aggregation_energy(DATA_PACKET_SIZE_BITS);
+
+        match self.link_cost {
+            LinkCost::TransmitEnergy => energy,
+            LinkCost::ResidualEnergyWeighted => {
+                let residual_ratio =
+                    (receiver_energy_j / INITIAL_NODE_ENERGY_J).max(MIN_RESIDUAL_RATIO);
+                energy / residual_ratio
+            }
+            LinkCost::HopCount => 1.0,
+        }
+    }
+
+    /// Cost of sending one aggregated packet directly to the base station,
+    /// `distance_to_sink` meters away.
+    fn sink_link_cost(&self, distance_to_sink: f32) -> f32 {
+        match self.link_cost {
+            LinkCost::TransmitEnergy | LinkCost::ResidualEnergyWeighted => {
+                calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance_to_sink)
+            }
+            LinkCost::HopCount => 1.0,
+        }
+    }
+
+    /// Builds the cluster-head graph for this round and computes each CH's
+    /// least-cost route to the sink.
+    ///
+    /// Vertex `n` (= number of CHs) is the sink; the cost matrix is dense
+    /// because every CH can reach every other CH within its radio range
+    /// (`link`). CH-to-CH distances are read from `distances` when a cache is given.
+    pub fn build_routes<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &S,
+        cluster_head_ids: &[usize],
+        distances: Option<&DistanceCache>,
+        link: &LinkLayer,
+    ) -> RoutingTable {
+        self.build_table(nodes, cluster_head_ids, distances, link, false)
+    }
+
+    /// Routing for protocols whose own CH → sink plan leaves some CHs without an
+    /// in-range hop: CHs within range of the sink transmit directly, the others
+    /// take their least-energy multi-hop path over in-range CH links.
+    pub fn build_fallback_routes<S: NodeStore + ?Sized>(
+        nodes: &S,
+        cluster_head_ids: &[usize],
+        distances: Option<&DistanceCache>,
+        link: &LinkLayer,
+    ) -> RoutingTable {
+        InterClusterRouter::new(LinkCost::TransmitEnergy, ShortestPath::Dijkstra)
+            .build_table(nodes, cluster_head_ids, distances, link, true)
+    }
+
+    /// Builds the routing table; with `direct_when_in_range` a CH that reaches the
+    /// sink gets no CH → CH links.
+    fn build_table<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &S,
+        cluster_head_ids: &[usize],
+        distances: Option<&DistanceCache>,
+        link: &LinkLayer,
+        direct_when_in_range: bool,
+    ) -> RoutingTable {
+        let n = cluster_head_ids.len();
+        let sink = n;
+
+        // costs[u * (n + 1) + v] = cost of link u → v (infinite when out of range)
+        let mut costs = vec![f32::INFINITY; (n + 1) * (n + 1)];
+        for (u, &u_id) in cluster_head_ids.iter().enumerate() {
+            let distance_to_sink = nodes.distance_to_base_station_m(u_id);
+            let sink_in_range = link.in_range(nodes, u_id, distance_to_sink);
+
+            if !(direct_when_in_range && sink_in_range) {
+                for (v, &v_id) in cluster_head_ids.iter().enumerate() {
+                    if u != v {
+                        let distance = node_distance(nodes, distances, u_id, v_id);
+                        if link.in_range(nodes, u_id, distance) {
+                            costs[u * (n + 1) + v] =
+                                self.cluster_head_link_cost(distance, nodes.remaining_energy_j(v_id));
+                        }
+                    }
+                }
+            }
+            if sink_in_range {
+                costs[u * (n + 1) + sink] = self.sink_link_cost(distance_to_sink);
+            }
+        }
+
+        let (path_costs, next_vertex) = match self.algorithm {
+            ShortestPath::Dijkstra => dijkstra_to_sink(&costs, n),
+            ShortestPath::BellmanFord => bellman_ford_to_sink(&costs, n),
+        };
+
+        let next_hops: Vec<NextHop> = next_vertex
+            .into_iter()
+            .zip(&path_costs)
+            .map(|(v, &cost)| {
+                if cost.is_infinite() {
+                    NextHop::Unreachable
+                } else if v == sink {
+                    NextHop::Sink
+                } else {
+                    NextHop::ClusterHead(cluster_head_ids[v])
+                }
+            })
+            .collect();
+
+        let hop_distances = cluster_head_ids
+            .iter()
+            .zip(&next_hops)
+            .map(|(&ch_id, &hop)| match hop {
+                NextHop::Sink => nodes.distance_to_base_station_m(ch_id),
+                NextHop::ClusterHead(next_id) => node_distance(nodes, distances, ch_id, next_id),
+                NextHop::Unreachable => 0.0,
+            })
+            .collect();
+
+        RoutingTable {
+            cluster_head_ids: cluster_head_ids.to_vec(),
+            next_hops,
+            path_costs,
+            hop_distances,
+        }
+    }
+}
+
+/// Dense O(V²) Dijkstra run backwards from the sink (vertex `n`).
+///
+/// Returns (path cost, next vertex) for every CH vertex `0..n`.
+fn dijkstra_to_sink(costs: &[f32], n: usize) -> (Vec<f32>, Vec<usize>) {
+    let stride = n + 1;
+    let sink = n;
+
+    let mut dist = vec![f32::INFINITY; stride];
+    let mut next = vec![sink; stride];
+    let mut settled = vec![false; stride];
+    dist[sink] = 0.0;
+
+    for _ in 0..stride {
+        // Pick the closest unsettled vertex
+        let mut v = None;
+        let mut best = f32::INFINITY;
+        for (i, &d) in dist.iter().enumerate() {
+            if !settled[i] && d < best {
+                best = d;
+                v = Some(i);
+            }
+        }
+
+        let Some(v) = v else { break };
+        settled[v] = true;
+
+        // Relax every incoming edge u → v
+        for u in 0..n {
+            if settled[u] {
+                continue;
+            }
+            let candidate = costs[u * stride + v] + dist[v];
+            if candidate < dist[u] {
+                dist[u] = candidate;
+                next[u] = v;
+            }
+        }
+    }
+
+    dist.truncate(n);
+    next.truncate(n);
+    (dist, next)
+}
+
+/// Bellman-Ford towards the sink (vertex `n`); stops early once no edge relaxes.
+///
+/// Returns (path cost, next vertex) for every CH vertex `0..n`.
+fn bellman_ford_to_sink(costs: &[f32], n: usize) -> (Vec<f32>, Vec<usize>) {
+    let stride = n + 1;
+    let sink = n;
+
+    let mut dist = vec![f32::INFINITY; stride];
+    let mut next = vec![sink; stride];
+    dist[sink] = 0.0;
+
+    for _ in 0..n {
+        let mut changed = false;
+
+        for u in 0..n {
+            for v in 0..stride {
+                let candidate = costs[u * stride + v] + dist[v];
+                if candidate < dist[u] {
+                    dist[u] = candidate;
+                    next[u] = v;
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    dist.truncate(n);
+    next.truncate(n);
+    (dist, next)
+}
+
+impl RoutingTable {
+    /// Cluster heads covered by this table.
+    pub fn cluster_head_ids(&self) -> &[usize] {
+        &self.cluster_head_ids
+    }
+
+    /// Next hop of the given cluster head (`None` if it is not part of the table).
+    pub fn next_hop(&self, ch_id: usize) -> Option<NextHop> {
+        self.cluster_head_ids
+            .iter()
+            .position(|&id| id == ch_id)
+            .map(|i| self.next_hops[i])
+    }
+
+    /// Total path cost from the given cluster head to the sink.
+    pub fn path_cost(&self, ch_id: usize) -> Option<f32> {
+        self.cluster_head_ids
+            .iter()
+            .position(|&id| id == ch_id)
+            .map(|i| self.path_costs[i])
+    }
+
+    /// Forwards every CH's aggregated packet one hop along its route over `link`
+    /// and deducts the corresponding energy.
+    ///
+    /// Each CH transmits exactly one aggregated packet (resent by ARQ if enabled);
+    /// a relaying CH merges incoming packets into its own, paying RX per attempt and
+    /// aggregation per received packet. `reading_origin_ids` lists the readings each
+    /// CH's aggregate carries (same order as `cluster_head_ids`); they are delivered
+    /// only if every hop of the CH's route succeeds, and with `field` their extra
+    /// compressed payload is charged on every hop they cross. CHs without any
+    /// in-range route do not transmit and are reported as isolated.
+    pub fn forward_to_sink<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &mut S,
+        reading_origin_ids: &[Vec<usize>],
+        link: &LinkLayer,
+        rng: &mut dyn RngCore,
+        metrics: &mut RoundMetrics,
+        mut field: Option<&mut FieldSensing>,
+    ) {
+        let mut hop_delivered = Vec::with_capacity(self.cluster_head_ids.len());
+        // Length and attempts of every CH's hop (`None` → not transmitted)
+        let mut hop_transmissions: Vec<Option<(f32, usize)>> = Vec::with_capacity(self.cluster_head_ids.len());
+
+        let hops = self.cluster_head_ids.iter().zip(&self.next_hops).zip(&self.hop_distances);
+        for ((&ch_id, &hop), &distance) in hops {
+            match hop {
+                NextHop::Sink => {
+                    let outcome = link.transmit(distance, rng);
+
+                    nodes.consume_energy(
+                        ch_id,
+                        calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance) * outcome.attempts as f32,
+                    );
+                    metrics.record_packets(ch_id, |packets| packets.sent_to_base_station += 1);
+                    metrics.record_link_outcome(ch_id, outcome);
+                    hop_delivered.push(outcome.delivered);
+                    hop_transmissions.push(Some((distance, outcome.attempts)));
+                }
+                NextHop::ClusterHead(next_id) => {
+                    let outcome = link.transmit(distance, rng);
+
+                    nodes.consume_energy(
+                        ch_id,
+                        calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance) * outcome.attempts as f32,
+                    );
+                    metrics.record_packets(ch_id, |packets| packets.sent_to_cluster_head += 1);
+                    metrics.record_link_outcome(ch_id, outcome);
+
+                    let mut receive_energy = calculate_receive_energy(DATA_PACKET_SIZE_BITS) * outcome.attempts as f32;
+                    if outcome.delivered {
+                        receive_energy += calculate_aggregation_energy(DATA_PACKET_SIZE_BITS);
+                        metrics.record_packets(next_id, |packets| {
+                            packets.aggregated += 1;
+                            packets.relayed += 1;
+                        });
+                    }
+                    nodes.consume_energy(next_id, receive_energy);
+                    hop_delivered.push(outcome.delivered);
+                    hop_transmissions.push(Some((distance, outcome.attempts)));
+                }
+                NextHop::Unreachable => {
+                    // Its readings are dropped below with the undelivered routes
+                    report_isolated(nodes, ch_id, &[], metrics);
+                    hop_delivered.push(false);
+                    hop_transmissions.push(None);
+                }
+            }
+        }
+
+        if let Some(field) = field.as_deref() {
+            self.charge_extra_payloads(nodes, field, reading_origin_ids, &hop_transmissions, &hop_delivered, metrics);
+        }
+
+        let reaches_sink = self.sink_reachability(&hop_delivered);
+        for (origin_ids, &reached) in reading_origin_ids.iter().zip(&reaches_sink) {
+            if reached {
+                if let Some(field) = field.as_deref_mut() {
+                    field.receive_aggregate(nodes, origin_ids);
+                }
+                metrics.record_delivered_readings(origin_ids);
+            } else {
+                metrics.record_dropped_readings(origin_ids);
+            }
+        }
+    }
+
+    /// Charges the extra compressed payload of every CH's aggregate on each hop of
+    /// its route up to the first failed one (which was still transmitted).
+    fn charge_extra_payloads<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &mut S,
+        field: &FieldSensing,
+        reading_origin_ids: &[Vec<usize>],
+        hop_transmissions: &[Option<(f32, usize)>],
+        hop_delivered: &[bool],
+        metrics: &mut RoundMetrics,
+    ) {
+        let index_of: HashMap<usize, usize> = self
+            .cluster_head_ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| (id, i))
+            .collect();
+
+        for (start, origin_ids) in reading_origin_ids.iter().enumerate() {
+            let extra_bits = field.extra_payload_bits(origin_ids);
+            if extra_bits <= 0.0 {
+                continue;
+            }
+
+            // Routes are loop-free, so the walk ends at the sink or a failed hop
+            let mut i = start;
+            while let Some((distance, attempts)) = hop_transmissions[i] {
+                let sender_id = self.cluster_head_ids[i];
+                let receiver_id = match self.next_hops[i] {
+                    NextHop::ClusterHead(next_id) => Some(next_id),
+                    NextHop::Sink | NextHop::Unreachable => None,
+                };
+                charge_extra_payload(nodes, sender_id, receiver_id, distance, attempts, extra_bits, metrics);
+
+                match receiver_id.and_then(|next_id| index_of.get(&next_id)) {
+                    Some(&next) if hop_delivered[i] => i = next,
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    /// Whether the packet of each CH (same order as `cluster_head_ids`) crosses every
+    /// hop of its route, given whether each CH's own hop succeeded.
+    fn sink_reachability(&self, hop_delivered: &[bool]) -> Vec<bool> {
+        let index_of: HashMap<usize, usize> = self
+            .cluster_head_ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| (id, i))
+            .collect();
+
+        let mut reaches: Vec<Option<bool>> = vec![None; self.cluster_head_ids.len()];
+        let mut path: Vec<usize> = Vec::new();
+
+        for start in 0..reaches.len() {
+            // Walk the route until its outcome is known (routes are loop-free)
+            let mut i = start;
+            let reached = loop {
+                if let Some(reached) = reaches[i] {
+                    break reached;
+                }
+                path.push(i);
+                if !hop_delivered[i] {
+                    break false;
+                }
+                match self.next_hops[i] {
+                    NextHop::Sink => break true,
+                    NextHop::Unreachable => break false,
+                    NextHop::ClusterHead(next_id) => match index_of.get(&next_id) {
+                        Some(&next) => i = next,
+                        None => break false,
+                    },
+                }
+            };
+
+            for i in path.drain(..) {
+                reaches[i] = Some(reached);
+            }
+        }
+
+        reaches.into_iter().map(|reached| reached.unwrap_or(false)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Cost of following `next` from vertex `u` to the sink (vertex `n`).
+    fn route_cost(costs: &[f32], next: &[usize], n: usize, mut u: usize) -> f32 {
+        let mut total = 0.0;
+        for _ in 0..=n {
+            if u == n {
+                return total;
+            }
+            total += costs[u * (n + 1) + next[u]];
+            u = next[u];
+        }
+        panic!("route from {u} loops");
+    }
+
+    #[test]
+    fn dijkstra_and_bellman_ford_agree() {
+        let mut rng = StdRng::seed_from_u64(4);
+
+        for n in [1, 2, 5, 12, 30] {
+            // Sparse random graph; vertex 0 never links to anything
+            let mut costs = vec![f32::INFINITY; (n + 1) * (n + 1)];
+            for u in 1..n {
+                for v in 0..=n {
+                    if u != v && rng.random_bool(0.3) {
+                        costs[u * (n + 1) + v] = rng.random_range(0.5..10.0);
+                    }
+                }
+            }
+
+            let (dijkstra_costs, dijkstra_next) = dijkstra_to_sink(&costs, n);
+            let (bellman_ford_costs, bellman_ford_next) = bellman_ford_to_sink(&costs, n);
+            assert!(dijkstra_costs[0].is_infinite());
+
+            for u in 0..n {
+                let (a, b) = (dijkstra_costs[u], bellman_ford_costs[u]);
+                assert!(a == b || (a - b).abs() <= 1e-4 * a, "vertex {u}: {a} vs {b}");
+
+                if a.is_finite() {
+                    assert!((route_cost(&costs, &dijkstra_next, n, u) - a).abs() <= 1e-4 * a);
+                    assert!((route_cost(&costs, &bellman_ford_next, n, u) - b).abs() <= 1e-4 * b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shortest_path_prefers_cheaper_relay() {
+        // 0 → sink costs 10, 0 → 1 → sink costs 2 + 3
+        let n = 2;
+        let mut costs = vec![f32::INFINITY; (n + 1) * (n + 1)];
+        costs[2] = 10.0;
+        costs[1] = 2.0;
+        costs[3 + 2] = 3.0;
+
+        for (path_costs, next) in [dijkstra_to_sink(&costs, n), bellman_ford_to_sink(&costs, n)] {
+            assert_eq!(path_costs, [5.0, 3.0]);
+            assert_eq!(next, [1, 2]);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::config::INITIAL_NODE_ENERGY_J;
+use crate::columns::NodeStore;
+use crate::spatial::SpatialGrid;
+use glam::Vec2;
+
+/// Normalized per-candidate inputs to a cluster-head scoring function.
+///
+/// All values lie in `[0, 1]`; distances are normalized by the diagonal of
+/// the actual deployment area rather than a fixed field size.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CandidateFeatures {
+    /// Remaining energy / initial energy
+    pub residual_energy: f32,
+
+    /// Distance to the cluster centroid / area diagonal
+    pub distance_to_centroid: f32,
+
+    /// Distance to the base station / area diagonal
+    pub distance_to_sink: f32,
+
+    /// Alive neighbours within the scoring radius / (alive nodes - 1)
+    pub node_degree: f32,
+
+    /// Rounds since this node was last a CH / recency horizon (1.0 = never or long ago)
+    pub rounds_since_cluster_head: f32,
+
+    /// Energy the node expects to harvest this round / the most any candidate
+    /// expects to harvest (0 at night or without energy harvesting)
+    pub predicted_harvest: f32,
+}
+
+/// Round-level values needed to normalize `CandidateFeatures`.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoringContext {
+    /// Diagonal of the deployment area (meters)
+    pub area_diagonal_m: f32,
+
+    /// Current simulation round
+    pub current_round: usize,
+
+    /// Number of rounds after which a former CH counts as fully "rested"
+    pub recency_horizon_rounds: usize,
+
+    /// Number of alive nodes this round
+    pub alive_node_count: usize,
+
+    /// Energy a full-size harvester is expected to collect this round (Joules,
+    /// 0 without energy harvesting)
+    pub predicted_harvest_j: f32,
+
+    /// Largest energy any CH candidate expects to harvest this round (Joules)
+    pub max_predicted_harvest_j: f32,
+}
+
+/// Anything that can rank cluster-head candidates (higher score = better CH).
+pub trait ClusterHeadScorer {
+    fn score(&self, features: &CandidateFeatures) -> f32;
+}
+
+impl<F> ClusterHeadScorer for F
+where
+    F: Fn(&CandidateFeatures) -> f32,
+{
+    fn score(&self, features: &CandidateFeatures) -> f32 {
+        self(features)
+    }
+}
+
+/// Linear scoring function:
+/// `score = w_e·energy - w_c·d_centroid - w_s·d_sink + w_d·degree + w_r·recency + w_h·harvest`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreWeights {
+    pub residual_energy: f32,
+    pub distance_to_centroid: f32,
+    pub distance_to_sink: f32,
+    pub node_degree: f32,
+    pub rounds_since_cluster_head: f32,
+    pub predicted_harvest: f32,
+}
+
+/// Named weight presets for `ScoreWeights`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoringPreset {
+    /// Original ZCR score: energy minus distance to centroid
+    EnergyCentrality,
+
+    /// Residual energy only
+    EnergyOnly,
+
+    /// Energy and centrality, plus a preference for CHs close to the sink
+    SinkAware,
+
+    /// Energy, centrality, sink distance, degree and recency with moderate weights
+    Balanced,
+
+    /// Energy and centrality, plus a preference for nodes expected to harvest
+    /// the most energy this round
+    HarvestAware,
+}
+
+impl ScoringPreset {
+    /// Weights associated with this preset.
+    pub fn weights(self) -> ScoreWeights {
+        let zero = ScoreWeights {
+            residual_energy: 0.0,
+            distance_to_centroid: 0.0,
+            distance_to_sink: 0.0,
+            node_degree: 0.0,
+            rounds_since_cluster_head: 0.0,
+            predicted_harvest: 0.0,
+        };
+
+        match self {
+            ScoringPreset::EnergyCentrality => ScoreWeights {
+                residual_energy: 1.0,
+                distance_to_centroid: 1.0,
+                ..zero
+            },
+            ScoringPreset::EnergyOnly => ScoreWeights {
+                residual_energy: 1.0,
+                ..zero
+            },
+            ScoringPreset::SinkAware => ScoreWeights {
+                residual_energy: 1.0,
+                distance_to_centroid: 0.5,
+                distance_to_sink: 0.5,
+                ..zero
+            },
+            ScoringPreset::Balanced => ScoreWeights {
+                residual_energy: 1.0,
+                distance_to_centroid: 0.5,
+                distance_to_sink: 0.25,
+                node_degree: 0.25,
+                rounds_since_cluster_head: 0.25,
+                predicted_harvest: 0.0,
+            },
+            ScoringPreset::HarvestAware => ScoreWeights {
+                residual_energy: 1.0,
+                distance_to_centroid: 1.0,
+                predicted_harvest: 1.0,
+                ..zero
+            },
+        }
+    }
+}
+
+impl From<ScoringPreset> for ScoreWeights {
+    fn from(preset: ScoringPreset) -> Self {
+        preset.weights()
+    }
+}
+
+impl ClusterHeadScorer for ScoreWeights {
+    fn score(&self, features: &CandidateFeatures) -> f32 {
+        self.residual_energy * features.residual_energy
+            - self.distance_to_centroid * features.distance_to_centroid
+            - self.distance_to_sink * features.distance_to_sink
+            + self.node_degree * features.node_degree
+            + self.rounds_since_cluster_head * features.rounds_since_cluster_head
+            + self.predicted_harvest * features.predicted_harvest
+    }
+}
+
+impl CandidateFeatures {
+    /// Computes the normalized features of node `id` as a CH candidate for the
+    /// cluster centered at `centroid`, given its precomputed alive-neighbour count.
+    pub fn extract<S: NodeStore + ?Sized>(
+        nodes: &S,
+        id: usize,
+        centroid: Vec2,
+        degree: usize,
+        context: &ScoringContext,
+    ) -> Self {
+        let rounds_since_cluster_head = match nodes.last_cluster_head_round(id) {
+            Some(round) => {
+                let elapsed = context.current_round.saturating_sub(round) as f32;
+                (elapsed / context.recency_horizon_rounds.max(1) as f32).min(1.0)
+            }
+            None => 1.0,
+        };
+
+        let possible_neighbours = context.alive_node_count.saturating_sub(1).max(1) as f32;
+
+        Self {
+            residual_energy: nodes.remaining_energy_j(id) / INITIAL_NODE_ENERGY_J,
+            distance_to_centroid: (nodes.position(id) - centroid).length() / context.area_diagonal_m,
+            distance_to_sink: nodes.distance_to_base_station_m(id) / context.area_diagonal_m,
+            node_degree: degree as f32 / possible_neighbours,
+            rounds_since_cluster_head,
+            predicted_harvest: if context.max_predicted_harvest_j > 0.0 {
+                (nodes.harvest_scale(id) * context.predicted_harvest_j / context.max_predicted_harvest_j).min(1.0)
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Counts, for every node, how many alive nodes lie within `radius_m` of it.
+///
+/// Dead nodes get a degree of 0.
+pub fn alive_node_degrees<S: NodeStore + ?Sized>(nodes: &S, radius_m: f32) -> Vec<usize> {
+    let grid = SpatialGrid::from_alive_nodes(nodes);
+    let mut degrees = vec![0; nodes.node_count()];
+
+    for node_id in (0..nodes.node_count()).filter(|&id| nodes.is_alive(id)) {
+        let mut neighbours = 0;
+        grid.for_each_within_radius(nodes.position(node_id), radius_m, |id, _| {
+            if id != node_id {
+                neighbours += 1;
+            }
+        });
+        degrees[node_id] = neighbours;
+    }
+
+    degrees
+}
@@ -1,8 +1,21 @@
 use crate::{
-    config::{SENSOR_VISUAL_RADIUS_PX, METERS_TO_PIXELS},
+    battery::{BatteryBank, BatteryConfig},
+    columns::{NodeColumns, NodeStore},
+    control::ControlPlane,
+    field::{sense_field, Aggregation, FieldSensing, SensorField},
+    harvest::{harvest_round, EnergyHarvester},
+    config::{BASE_STATION_POSITION, SENSOR_VISUAL_RADIUS_PX, METERS_TO_PIXELS, ROUND_DURATION_S, STEADY_STATE_FRAMES_PER_ROUND},
+    distance::{DistanceCache, DistanceCacheMode},
+    link::LinkLayer,
+    mac::MacConfig,
+    metrics::RoundMetrics,
     node::Node,
+    power::{DutyCycle, PowerModel},
 };
 use macroquad::prelude::*;
+use std::borrow::Cow;
+use ::rand::SeedableRng;
+use ::rand::rngs::StdRng;
 
 /// Common trait for different WSN protocols (currently mainly LEACH).
 pub trait Protocol {
@@ -12,45 +25,303 @@ pub trait Protocol {
     /// Human-readable name of the protocol (used in logs/UI/etc.).
     fn name(&self) -> &'static str;
 
+    /// How long nodes keep their radio on (used by the simulator's power model).
+    fn duty_cycle(&self, _simulator: &Simulator) -> DutyCycle {
+        DutyCycle::always_on()
+    }
 }
 
 /// Central simulation state — holds the network and current round information.
 pub struct Simulator {
-    /// All sensor nodes in the network
-    pub nodes: Vec<Node>,
+    /// All sensor nodes in the network (empty with columnar storage; see `nodes()`)
+    pub(crate) nodes: Vec<Node>,
+
+    /// Optional struct-of-arrays storage that owns the nodes instead of `nodes`
+    pub(crate) columns: Option<NodeColumns>,
 
     /// Current simulation round number (starts at 0)
     pub current_round: usize,
 
     /// How many nodes still have energy > 0
     pub alive_node_count: usize,
+
+    /// Width of the deployment area (meters)
+    pub area_width_m: f32,
+
+    /// Height of the deployment area (meters)
+    pub area_height_m: f32,
+
+    /// Statistics of the most recent round
+    pub metrics: RoundMetrics,
+
+    /// Source of all randomness in the run (deployment, CH election, clustering)
+    pub rng: StdRng,
+
+    /// Optional precomputed node-to-node distances, refreshed before every round
+    pub distance_cache: Option<DistanceCache>,
+
+    /// Packet reception model and ARQ used for every data transmission
+    pub link: LinkLayer,
+
+    /// Optional MAC layer (CSMA advertisements, TDMA data phase); `None` → collision-free channel
+    pub mac: Option<MacConfig>,
+
+    /// Optional charging of cluster-setup control messages; `None` → setup is free
+    pub control: Option<ControlPlane>,
+
+    /// Steady-state data frames per round (cluster setup is paid once per round)
+    pub frames_per_round: usize,
+
+    /// Optional per-state power draws (idle, sleep, sensing, MCU); `None` → only
+    /// packets cost energy
+    pub power: Option<PowerModel>,
+
+    /// Optional battery of every node; `None` → `remaining_energy_j` is an ideal
+    /// linear counter
+    pub battery: Option<BatteryBank>,
+
+    /// Optional energy harvester of every node; `None` → energy only ever decreases
+    pub harvester: Option<EnergyHarvester>,
+
+    /// Optional synthetic field the nodes sample and the sink reconstructs;
+    /// `None` → readings carry no values
+    pub field: Option<FieldSensing>,
+
+    /// Whether protocols evaluate `metrics.cluster_quality` every round (off by
+    /// default: the silhouette score takes O(N²) time and one allocation per node,
+    /// more than the rest of a round from ~10k nodes on)
+    pub collect_cluster_quality: bool,
 }
 
 impl Simulator {
     /// Creates a new simulator with randomly placed nodes.
     pub fn new(width: f32, height: f32, node_count: usize) -> Self {
-        let nodes = Node::create_wsn(width, height, node_count);
+        Self::from_rng(width, height, node_count, StdRng::from_os_rng())
+    }
+
+    /// Creates a new simulator whose deployment and protocol decisions are
+    /// fully reproducible from `seed`.
+    pub fn with_seed(width: f32, height: f32, node_count: usize, seed: u64) -> Self {
+        Self::from_rng(width, height, node_count, StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(width: f32, height: f32, node_count: usize, mut rng: StdRng) -> Self {
+        let nodes = Node::create_wsn(width, height, node_count, &mut rng);
 
         Self {
             nodes,
+            columns: None,
             current_round: 0,
             alive_node_count: node_count,
+            area_width_m: width,
+            area_height_m: height,
+            metrics: RoundMetrics::default(),
+            collect_cluster_quality: false,
+            rng,
+            distance_cache: None,
+            link: LinkLayer::default(),
+            mac: None,
+            control: None,
+            frames_per_round: STEADY_STATE_FRAMES_PER_ROUND,
+            power: None,
+            battery: None,
+            harvester: None,
+            field: None,
+        }
+    }
+
+    /// Stores the nodes as struct-of-arrays (`NodeColumns`), which keeps large
+    /// networks cache-friendly; every round runs on the columns.
+    pub fn with_columnar_storage(mut self) -> Self {
+        if self.columns.is_none() {
+            self.columns = Some(NodeColumns::from(&self.nodes[..]));
+            self.nodes = Vec::new();
+        }
+        self
+    }
+
+    /// Evaluates `metrics.cluster_quality` every round; the silhouette score makes
+    /// this O(N²) per round.
+    pub fn with_cluster_quality(mut self) -> Self {
+        self.collect_cluster_quality = true;
+        self
+    }
+
+    /// Precomputes node-to-node distances (full matrix or neighbour tables) for
+    /// the protocols to reuse every round.
+    pub fn with_distance_cache(mut self, mode: DistanceCacheMode) -> Self {
+        self.distance_cache = Some(DistanceCache::new(mode, self.node_store()));
+        self
+    }
+
+    /// Makes data transmissions lossy (and optionally retransmitted) according to `link`.
+    pub fn with_link_layer(mut self, link: LinkLayer) -> Self {
+        self.link = link;
+        self
+    }
+
+    /// Simulates channel access within each round: CH advertisements contend with
+    /// CSMA and members send their data in TDMA slots, so packets can collide.
+    pub fn with_mac(mut self, mac: MacConfig) -> Self {
+        self.mac = Some(mac);
+        self
+    }
+
+    /// Charges every protocol for its cluster-setup control messages.
+    pub fn with_control_plane(mut self, control: ControlPlane) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// Sends `frames` rounds of member data between two cluster setups (at least one).
+    pub fn with_frames_per_round(mut self, frames: usize) -> Self {
+        self.frames_per_round = frames.max(1);
+        self
+    }
+
+    /// Charges nodes for the time they spend in each power state every round.
+    pub fn with_power_model(mut self, power: PowerModel) -> Self {
+        self.power = Some(power);
+        self
+    }
+
+    /// Gives every node a battery of its current energy; nodes die when the
+    /// battery model says they are depleted.
+    pub fn with_battery(mut self, config: BatteryConfig) -> Self {
+        self.battery = Some(BatteryBank::new(config, self.node_store()));
+        self
+    }
+
+    /// Recharges nodes from `harvester` after every round and revives dead nodes
+    /// once they stored enough energy.
+    pub fn with_energy_harvesting(mut self, harvester: EnergyHarvester) -> Self {
+        self.harvester = Some(harvester);
+        self
+    }
+
+    /// Sizes every node's energy harvester (`Node::harvest_scale`) with `scale`,
+    /// e.g. to leave shaded nodes or nodes without a panel at 0.
+    pub fn with_harvest_scales(mut self, scale: impl Fn(&Node) -> f32) -> Self {
+        for id in 0..self.node_store().node_count() {
+            self.edit_node(id, |node| node.harvest_scale = scale(node));
+        }
+        self
+    }
+
+    /// Lets nodes sample a synthetic field with randomly placed moving hotspots,
+    /// which CHs aggregate with `aggregation` and the sink reconstructs.
+    pub fn with_sensor_field(mut self, aggregation: Aggregation) -> Self {
+        let field = SensorField::random(self.area_width_m, self.area_height_m, &mut self.rng);
+        self.field = Some(FieldSensing::new(field, aggregation));
+        self
+    }
+
+    /// Adds an alive node at `position` and returns its id.
+    pub fn add_node(&mut self, position: ::glam::Vec2) -> usize {
+        let id = self.node_store().node_count();
+        let node = Node::new(id, position);
+        match &mut self.columns {
+            Some(columns) => columns.push(&node),
+            None => self.nodes.push(node),
+        }
+        self.alive_node_count += 1;
+
+        if let Some(cache) = &mut self.distance_cache {
+            cache.invalidate();
+        }
+
+        id
+    }
+
+    /// Moves node `id` to `position` (updating its distance to the base station).
+    pub fn move_node(&mut self, id: usize, position: ::glam::Vec2) {
+        self.edit_node(id, |node| {
+            node.position = position;
+            node.distance_to_base_station_m = (BASE_STATION_POSITION - position).length();
+        });
+
+        if let Some(cache) = &mut self.distance_cache {
+            cache.invalidate();
+        }
+    }
+
+    /// Applies `edit` to node `id` between rounds.
+    ///
+    /// With columnar storage `edit` works on a copy that is written back, and
+    /// changes to `cluster_member_ids` are ignored. Moving a node this way leaves
+    /// `distance_to_base_station_m` and the distance cache to the caller; see
+    /// `move_node`.
+    pub fn edit_node(&mut self, id: usize, edit: impl FnOnce(&mut Node)) {
+        match &mut self.columns {
+            Some(columns) => {
+                let mut node = columns.node(id);
+                edit(&mut node);
+                columns.set_node(id, &node);
+            }
+            None => edit(&mut self.nodes[id]),
         }
     }
 
+    /// All nodes as `Node`s: borrowed by default, built on every call with
+    /// columnar storage.
+    pub fn nodes(&self) -> Cow<'_, [Node]> {
+        match &self.columns {
+            Some(columns) => Cow::Owned(columns.to_nodes()),
+            None => Cow::Borrowed(&self.nodes),
+        }
+    }
+
+    /// Length of the deployment area's diagonal (meters) — the largest possible
+    /// distance between two nodes, used to normalize distances.
+    pub fn area_diagonal_m(&self) -> f32 {
+        (self.area_width_m.powi(2) + self.area_height_m.powi(2)).sqrt()
+    }
+
+    /// Node storage the rounds run on: `columns` with columnar storage, `nodes`
+    /// otherwise.
+    pub fn node_store(&self) -> &dyn NodeStore {
+        match &self.columns {
+            Some(columns) => columns,
+            None => &self.nodes,
+        }
+    }
+
+    /// Mutable access to the node storage the rounds run on.
+    pub fn node_store_mut(&mut self) -> &mut dyn NodeStore {
+        active_store(&mut self.nodes, &mut self.columns)
+    }
+
+    /// Length of one round (seconds), as set by the power model.
+    pub fn round_duration_s(&self) -> f32 {
+        self.power.map_or(ROUND_DURATION_S, |power| power.round_duration_s)
+    }
+
+    /// Energy a full-size harvester is expected to collect during the current
+    /// round (Joules, 0 without energy harvesting).
+    pub fn predicted_harvest_j(&self) -> f32 {
+        self.harvester.as_ref().map_or(0.0, |harvester| {
+            harvester.predicted_energy_j(self.current_round, self.round_duration_s())
+        })
+    }
+
     /// Draws all nodes on screen using Macroquad.
-    /// Colors indicate status: dead (dark red), cluster head (green), normal (light yellow).
+    /// Colors indicate status: dead (dark red), cluster head (green), isolated (grey),
+    /// normal (light yellow).
     pub fn render(&self) {
-        for node in self.nodes.iter() {
-            let color = if !node.is_alive {
+        let nodes = self.node_store();
+
+        for id in 0..nodes.node_count() {
+            let color = if !nodes.is_alive(id) {
                 Color::from_rgba(180, 60, 60, 255)   // dead - dark red
-            } else if node.is_cluster_head {
+            } else if nodes.is_cluster_head(id) {
                 Color::from_rgba(89, 172, 119, 255)  // cluster head - green
+            } else if nodes.is_isolated(id) {
+                Color::from_rgba(130, 130, 140, 255) // isolated - grey
             } else {
                 Color::from_rgba(245, 235, 200, 255) // normal alive node - light yellow
             };
 
-            let screen_position = node.position * METERS_TO_PIXELS;
+            let screen_position = nodes.position(id) * METERS_TO_PIXELS;
 
             draw_circle(
                 screen_position.x,
@@ -64,6 +335,49 @@ impl Simulator {
     /// Advances simulation by one round and lets the protocol do its work.
     pub fn update<P: Protocol>(&mut self, protocol: &mut P) {
         self.current_round += 1;
+        self.metrics = RoundMetrics::default();
+
+        // Nodes may have been added or moved since the last round
+        let Simulator { nodes, columns, distance_cache, .. } = self;
+        if let Some(cache) = distance_cache {
+            cache.refresh(active_store(nodes, columns));
+        }
+
+        let energy_before_j: Option<Vec<f32>> = self.battery.as_ref().map(|_| {
+            let nodes = self.node_store();
+            (0..nodes.node_count()).map(|id| nodes.remaining_energy_j(id)).collect()
+        });
+
         protocol.run_round(self);
+        sense_field(self);
+
+        if let Some(power) = self.power {
+            let duty_cycle = protocol.duty_cycle(self);
+            let Simulator { nodes, columns, frames_per_round, metrics, .. } = self;
+            power.charge_round(active_store(nodes, columns), &duty_cycle, *frames_per_round, metrics);
+        }
+
+        // The battery sees the round's total draw spread over the round
+        let round_duration_s = self.round_duration_s();
+        let Simulator { nodes, columns, battery, metrics, .. } = self;
+        if let (Some(battery), Some(energy_before_j)) = (battery, energy_before_j) {
+            battery.apply_round(active_store(nodes, columns), &energy_before_j, round_duration_s, metrics);
+        }
+
+        harvest_round(self);
+    }
+}
+
+/// The node storage the round phases run on: `columns` when set, `nodes` otherwise.
+///
+/// Takes the two fields rather than the simulator so phases can borrow the other
+/// fields alongside it.
+pub(crate) fn active_store<'a>(
+    nodes: &'a mut Vec<Node>,
+    columns: &'a mut Option<NodeColumns>,
+) -> &'a mut dyn NodeStore {
+    match columns {
+        Some(columns) => columns,
+        None => nodes,
     }
 }
@@ -0,0 +1,368 @@
+use crate::columns::NodeStore;
+use glam::Vec2;
+
+/// Average number of points per cell targeted by `SpatialGrid::new`
+const POINTS_PER_CELL: f32 = 2.0;
+
+/// Uniform-grid spatial index over a fixed set of `(id, position)` points.
+///
+/// - Built in O(N) with a counting sort of the points into square cells
+/// - Nearest / k-nearest queries search rings of cells outwards from the query point
+///   and stop as soon as no unvisited cell can hold a closer point
+/// - Radius queries only visit the cells overlapping the query circle
+///
+/// Ties between equally distant points are broken by the smaller id.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    /// Lower-left corner of the grid (bounding box of the points)
+    origin: Vec2,
+
+    /// Side length of one square cell (meters)
+    cell_size_m: f32,
+
+    /// Number of cell columns
+    cols: usize,
+
+    /// Number of cell rows
+    rows: usize,
+
+    /// Offset of every cell's first entry in `entries` (`cols * rows + 1` values)
+    cell_starts: Vec<usize>,
+
+    /// Points sorted by cell
+    entries: Vec<(usize, Vec2)>,
+}
+
+impl SpatialGrid {
+    /// Builds a grid whose cell size gives about two points per cell.
+    pub fn new(points: impl IntoIterator<Item = (usize, Vec2)>) -> Self {
+        let points: Vec<(usize, Vec2)> = points.into_iter().collect();
+        let (min, max) = bounding_box(&points);
+        let extent = max - min;
+        let n = points.len().max(1) as f32;
+
+        let area = extent.x * extent.y;
+        let cell_size_m = if area > 0.0 {
+            (area * POINTS_PER_CELL / n).sqrt()
+        } else if extent.max_element() > 0.0 {
+            // Collinear points
+            extent.max_element() * POINTS_PER_CELL / n
+        } else {
+            1.0
+        };
+
+        Self::build(points, min, max, cell_size_m)
+    }
+
+    /// Builds a grid with a fixed cell size (meters).
+    pub fn with_cell_size(points: impl IntoIterator<Item = (usize, Vec2)>, cell_size_m: f32) -> Self {
+        let points: Vec<(usize, Vec2)> = points.into_iter().collect();
+        let (min, max) = bounding_box(&points);
+
+        Self::build(points, min, max, cell_size_m.max(f32::EPSILON))
+    }
+
+    /// Indexes the nodes listed in `ids` under their node id.
+    pub fn from_nodes<S: NodeStore + ?Sized>(nodes: &S, ids: &[usize]) -> Self {
+        Self::new(ids.iter().map(|&id| (id, nodes.position(id))))
+    }
+
+    /// Indexes every alive node under its node id.
+    pub fn from_alive_nodes<S: NodeStore + ?Sized>(nodes: &S) -> Self {
+        Self::new(
+            (0..nodes.node_count())
+                .filter(|&id| nodes.is_alive(id))
+                .map(|id| (id, nodes.position(id))),
+        )
+    }
+
+    /// Number of indexed points.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the grid holds no points.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Closest point to `point` as `(id, distance)`, or `None` if the grid is empty.
+    pub fn nearest(&self, point: Vec2) -> Option<(usize, f32)> {
+        let (cx, cy) = self.cell_coords(point);
+        let mut best: Option<(usize, f32)> = None;
+
+        for ring in 0..=self.max_ring(cx, cy) {
+            self.visit_ring(cx, cy, ring, |cell| {
+                for &(id, position) in self.cell_entries(cell) {
+                    let distance = (point - position).length();
+                    if best.is_none_or(|(best_id, best_distance)| {
+                        (distance, id) < (best_distance, best_id)
+                    }) {
+                        best = Some((id, distance));
+                    }
+                }
+            });
+
+            // Every unvisited cell is at least `ring` cells away
+            if let Some((_, distance)) = best
+                && distance <= ring as f32 * self.cell_size_m
+            {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Up to `k` closest points to `point` as `(id, distance)`, nearest first.
+    pub fn k_nearest(&self, point: Vec2, k: usize) -> Vec<(usize, f32)> {
+        let mut candidates: Vec<(usize, f32)> = Vec::new();
+        if k == 0 {
+            return candidates;
+        }
+
+        let (cx, cy) = self.cell_coords(point);
+
+        for ring in 0..=self.max_ring(cx, cy) {
+            self.visit_ring(cx, cy, ring, |cell| {
+                for &(id, position) in self.cell_entries(cell) {
+                    candidates.push((id, (point - position).length()));
+                }
+            });
+
+            if candidates.len() >= k {
+                candidates.select_nth_unstable_by(k - 1, compare_by_distance);
+                candidates.truncate(k);
+
+                let kth_distance = candidates.iter().map(|&(_, d)| d).fold(0.0, f32::max);
+                if kth_distance <= ring as f32 * self.cell_size_m {
+                    break;
+                }
+            }
+        }
+
+        candidates.sort_unstable_by(compare_by_distance);
+        candidates.truncate(k);
+        candidates
+    }
+
+    /// All points within `radius_m` of `point` (inclusive) as `(id, distance)`,
+    /// in no particular order.
+    pub fn within_radius(&self, point: Vec2, radius_m: f32) -> Vec<(usize, f32)> {
+        let mut found = Vec::new();
+        self.for_each_within_radius(point, radius_m, |id, distance| found.push((id, distance)));
+        found
+    }
+
+    /// Calls `f(id, distance)` for every point within `radius_m` of `point` (inclusive).
+    pub fn for_each_within_radius(&self, point: Vec2, radius_m: f32, mut f: impl FnMut(usize, f32)) {
+        if self.is_empty() || radius_m < 0.0 {
+            return;
+        }
+
+        let (x0, y0) = self.cell_coords(point - Vec2::splat(radius_m));
+        let (x1, y1) = self.cell_coords(point + Vec2::splat(radius_m));
+
+        let x_range = x0.max(0)..=x1.min(self.cols as i64 - 1);
+        let y_range = y0.max(0)..=y1.min(self.rows as i64 - 1);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                for &(id, position) in self.cell_entries(self.cell_index(x, y)) {
+                    let distance = (point - position).length();
+                    if distance <= radius_m {
+                        f(id, distance);
+                    }
+                }
+            }
+        }
+    }
+
+    fn build(points: Vec<(usize, Vec2)>, min: Vec2, max: Vec2, cell_size_m: f32) -> Self {
+        let extent = max - min;
+        let cols = (extent.x / cell_size_m).floor() as usize + 1;
+        let rows = (extent.y / cell_size_m).floor() as usize + 1;
+
+        let mut grid = Self {
+            origin: min,
+            cell_size_m,
+            cols,
+            rows,
+            cell_starts: vec![0; cols * rows + 1],
+            entries: Vec::with_capacity(points.len()),
+        };
+
+        // Counting sort of the points into their cells
+        let cells: Vec<usize> = points
+            .iter()
+            .map(|&(_, position)| {
+                let (x, y) = grid.cell_coords(position);
+                grid.cell_index(x, y)
+            })
+            .collect();
+
+        for &cell in &cells {
+            grid.cell_starts[cell + 1] += 1;
+        }
+        for cell in 0..cols * rows {
+            grid.cell_starts[cell + 1] += grid.cell_starts[cell];
+        }
+
+        let mut next = grid.cell_starts.clone();
+        let mut entries = vec![(0, Vec2::ZERO); points.len()];
+        for (&point, &cell) in points.iter().zip(&cells) {
+            entries[next[cell]] = point;
+            next[cell] += 1;
+        }
+        grid.entries = entries;
+
+        grid
+    }
+
+    /// Unclamped cell coordinates of `point` (may lie outside the grid).
+    fn cell_coords(&self, point: Vec2) -> (i64, i64) {
+        let relative = (point - self.origin) / self.cell_size_m;
+        (relative.x.floor() as i64, relative.y.floor() as i64)
+    }
+
+    /// Flat index of an in-grid cell; points on the max edge land in the last cell.
+    fn cell_index(&self, x: i64, y: i64) -> usize {
+        let x = x.clamp(0, self.cols as i64 - 1) as usize;
+        let y = y.clamp(0, self.rows as i64 - 1) as usize;
+        y * self.cols + x
+    }
+
+    fn cell_entries(&self, cell: usize) -> &[(usize, Vec2)] {
+        &self.entries[self.cell_starts[cell]..self.cell_starts[cell + 1]]
+    }
+
+    /// Ring (Chebyshev distance in cells) beyond which no grid cell lies.
+    fn max_ring(&self, cx: i64, cy: i64) -> usize {
+        let dx = cx.abs().max((self.cols as i64 - 1 - cx).abs());
+        let dy = cy.abs().max((self.rows as i64 - 1 - cy).abs());
+        dx.max(dy) as usize
+    }
+
+    /// Calls `f` for every in-grid cell exactly `ring` cells away from `(cx, cy)`.
+    fn visit_ring(&self, cx: i64, cy: i64, ring: usize, mut f: impl FnMut(usize)) {
+        let r = ring as i64;
+        let in_cols = |x: i64| (0..self.cols as i64).contains(&x);
+        let in_rows = |y: i64| (0..self.rows as i64).contains(&y);
+
+        if r == 0 {
+            if in_cols(cx) && in_rows(cy) {
+                f(self.cell_index(cx, cy));
+            }
+            return;
+        }
+
+        let x_range = (cx - r).max(0)..=(cx + r).min(self.cols as i64 - 1);
+        for y in [cy - r, cy + r] {
+            if in_rows(y) {
+                for x in x_range.clone() {
+                    f(self.cell_index(x, y));
+                }
+            }
+        }
+
+        let y_range = (cy - r + 1).max(0)..=(cy + r - 1).min(self.rows as i64 - 1);
+        for x in [cx - r, cx + r] {
+            if in_cols(x) {
+                for y in y_range.clone() {
+                    f(self.cell_index(x, y));
+                }
+            }
+        }
+    }
+}
+
+fn bounding_box(points: &[(usize, Vec2)]) -> (Vec2, Vec2) {
+    if points.is_empty() {
+        return (Vec2::ZERO, Vec2::ZERO);
+    }
+
+    points.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), &(_, position)| (min.min(position), max.max(position)),
+    )
+}
+
+fn compare_by_distance(a: &(usize, f32), b: &(usize, f32)) -> std::cmp::Ordering {
+    a.1.total_cmp(&b.1).then(a.0.cmp(&b.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_points(count: usize, rng: &mut StdRng) -> Vec<(usize, Vec2)> {
+        (0..count)
+            .map(|id| (id, Vec2::new(rng.random_range(0.0..100.0), rng.random_range(0.0..60.0))))
+            .collect()
+    }
+
+    /// Query points inside and around the points' bounding box.
+    fn random_queries(rng: &mut StdRng) -> Vec<Vec2> {
+        (0..50)
+            .map(|_| Vec2::new(rng.random_range(-30.0..130.0), rng.random_range(-30.0..90.0)))
+            .collect()
+    }
+
+    /// Every point as `(id, distance)` to `query`, nearest first (ties by id).
+    fn brute_force(points: &[(usize, Vec2)], query: Vec2) -> Vec<(usize, f32)> {
+        let mut all: Vec<(usize, f32)> = points
+            .iter()
+            .map(|&(id, position)| (id, (query - position).length()))
+            .collect();
+        all.sort_unstable_by(compare_by_distance);
+        all
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let points = random_points(300, &mut rng);
+        let grid = SpatialGrid::new(points.clone());
+
+        for query in random_queries(&mut rng) {
+            assert_eq!(grid.nearest(query), brute_force(&points, query).first().copied());
+        }
+        assert_eq!(SpatialGrid::new([]).nearest(Vec2::ZERO), None);
+    }
+
+    #[test]
+    fn k_nearest_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let points = random_points(300, &mut rng);
+        let grid = SpatialGrid::new(points.clone());
+
+        for query in random_queries(&mut rng) {
+            let expected = brute_force(&points, query);
+            for k in [0, 1, 7, 300, 400] {
+                assert_eq!(grid.k_nearest(query, k), expected[..k.min(expected.len())]);
+            }
+        }
+    }
+
+    #[test]
+    fn within_radius_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let points = random_points(300, &mut rng);
+        let grid = SpatialGrid::with_cell_size(points.clone(), 7.5);
+
+        for query in random_queries(&mut rng) {
+            for radius_m in [0.0, 5.0, 20.0, 200.0] {
+                let mut found = grid.within_radius(query, radius_m);
+                found.sort_unstable_by(compare_by_distance);
+
+                let expected: Vec<(usize, f32)> = brute_force(&points, query)
+                    .into_iter()
+                    .filter(|&(_, distance)| distance <= radius_m)
+                    .collect();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+}
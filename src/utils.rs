@@ -29,11 +29,12 @@ pub(crate) fn calculate_transmit_energy(data_size_bits: f32, distance_m: f32) ->
 
 /// Resets a node's protocol-specific state at the start of a new round.
 ///
-/// Clears cluster head status, assigned cluster head, and member list.
+/// Clears cluster head status, assigned cluster head, member list and isolation.
 pub(crate) fn reset_node_for_new_round(node: &mut Node) {
     node.is_cluster_head = false;
     node.cluster_head_id = None;
     node.cluster_member_ids.clear();
+    node.is_isolated = false;
 }
 
 /// Calculates the energy consumed when receiving data (electronics only).
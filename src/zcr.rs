@@ -1,35 +1,128 @@
 use core::f32;
-use crate::clustering::KMeans;
+use crate::cluster_count::ClusterCount;
+use crate::columns::NodeStore;
+use crate::distance::{node_distance, DistanceCache};
+use crate::clustering::{CentroidInit, ClusterQuality, Clusterer, KMeans};
 use crate::config::{
+    CH_SCORING_NEIGHBOUR_RADIUS_M,
     INITIAL_NODE_ENERGY_J,
     DATA_PACKET_SIZE_BITS,
     FS_MULTIPATH_THRESHOLD_DISTANCE_M,
+    ZCR_MAX_RELAYS_PER_CLUSTER_HEAD,
+    ZCR_RECLUSTER_ENERGY_DROP_FRACTION,
+    ZCR_RECLUSTER_INTERVAL_ROUNDS,
+    ZCR_RECLUSTER_ON_CLUSTER_HEAD_DEATH,
 };
-use crate::node::Node;
-use crate::simulator::{Protocol, Simulator};
+use crate::field::charge_extra_payload;
+use crate::frame::start_data_frame;
+use crate::handover::{collect_member_data, HandoverConfig};
+use crate::link::{send_to_sink, LinkLayer};
+use crate::mac::schedule_tdma;
+use crate::metrics::RoundMetrics;
+use crate::power::DutyCycle;
+use crate::routing::InterClusterRouter;
+use crate::scoring::{
+    alive_node_degrees,
+    CandidateFeatures,
+    ClusterHeadScorer,
+    ScoringContext,
+    ScoringPreset,
+};
+use crate::simulator::{active_store, Protocol, Simulator};
+use crate::spatial::SpatialGrid;
 use crate::utils::{
     calculate_aggregation_energy,
     calculate_receive_energy,
-    reset_node_for_new_round,
     calculate_transmit_energy,
 };
+use rand::RngCore;
+
+/// Events that make ZCR re-cluster before its regular interval has elapsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReclusterTriggers {
+    /// Re-cluster when a cluster head of the previous round has died
+    pub on_cluster_head_death: bool,
+
+    /// Re-cluster when a cluster's residual energy drops below this fraction of
+    /// its energy at clustering time (`None` → disabled)
+    pub cluster_energy_drop_fraction: Option<f32>,
+}
+
+impl Default for ReclusterTriggers {
+    fn default() -> Self {
+        Self {
+            on_cluster_head_death: ZCR_RECLUSTER_ON_CLUSTER_HEAD_DEATH,
+            cluster_energy_drop_fraction: Some(ZCR_RECLUSTER_ENERGY_DROP_FRACTION),
+        }
+    }
+}
 
 /// ZCR: Zone-based Cluster Routing (proposed variant)
-/// - Uses K-Means to partition nodes into spatial clusters
-/// - Selects one "best" cluster head per cluster using energy + distance-to-centroid score
+/// - Uses K-Means (or any other `Clusterer`) to partition nodes into spatial clusters,
+///   kept fixed for a configurable number of rounds (or until a re-clustering trigger fires)
+/// - Between re-clusterings, the CH role rotates among the members of each cluster
+/// - Selects one "best" cluster head per cluster using a pluggable scoring function
+///   (default: energy + distance-to-centroid)
 /// - Divides cluster heads into two zones: near (< threshold) and far
-/// - Far-zone CHs may relay through the nearest near-zone CH if closer than direct to sink
+/// - Far-zone CHs may relay through a near-zone CH chosen by energy-weighted path cost,
+///   with a per-CH cap on accepted relays
+/// - Optionally, the zone relay is replaced by full CH-level multi-hop routing
+/// - Every data hop crosses the simulator's link layer and may be lost
+/// - With the simulator's MAC layer enabled, members send in TDMA slots and may
+///   collide with neighbouring clusters (CH assignment is centralized, so there is
+///   no advertisement contention)
+/// - With the simulator's control plane enabled, status reports to the BS, the BS's
+///   assignments and TDMA schedules cost energy
+/// - Clusters are set up once per round; members then send one packet in each of
+///   the simulator's steady-state frames
 pub struct Zcr {
     /// Number of cluster heads selected for the current round
     num_cluster_heads: usize,
 
-    /// Desired probability used to compute expected number of CHs
+    /// Desired CH probability (sets the default cluster count and the CH recency horizon)
     cluster_head_probability: f32,
 
+    /// Strategy that decides how many clusters to form at each re-clustering
+    cluster_count: ClusterCount,
+
     /// Zone separation of cluster heads:
     /// [0] = far-zone CHs (distance to sink > threshold)
     /// [1] = near-zone CHs  (distance to sink ≤ threshold)
     zone_cluster_heads: Vec<Vec<usize>>,
+
+    /// Maximum number of far-zone CHs one near-zone CH relays for per round
+    max_relays_per_cluster_head: usize,
+
+    /// Optional CH-level multi-hop routing that replaces the single zone relay hop
+    routing: Option<InterClusterRouter>,
+
+    /// Scoring function used to pick the best CH candidate of each cluster
+    scorer: Box<dyn ClusterHeadScorer>,
+
+    /// Optional mid-round CH handover (`None` → a depleted CH finishes the round
+    /// and dies afterwards)
+    handover: Option<HandoverConfig>,
+
+    /// Clustering algorithm; its fitted clusters are kept across rounds
+    clusterer: Box<dyn Clusterer>,
+
+    /// Whether `clusterer` holds a clustering yet (false until the first round)
+    is_clustered: bool,
+
+    /// Number of rounds the clusters are kept before re-clustering
+    recluster_interval_rounds: usize,
+
+    /// Early re-clustering conditions
+    recluster_triggers: ReclusterTriggers,
+
+    /// Rounds elapsed since the last re-clustering (0 in the round it happened)
+    rounds_since_reclustering: usize,
+
+    /// Total residual energy of each cluster when it was formed
+    cluster_energy_at_clustering: Vec<f32>,
+
+    /// Cluster heads selected in the previous round (one slot per cluster)
+    previous_cluster_head_ids: Vec<Option<usize>>,
 }
 
 impl Zcr {
@@ -37,16 +130,233 @@ impl Zcr {
         Self {
             num_cluster_heads: 0,
             cluster_head_probability,
+            cluster_count: ClusterCount::Probability(cluster_head_probability),
             zone_cluster_heads: vec![Vec::new(), Vec::new()],
+            max_relays_per_cluster_head: ZCR_MAX_RELAYS_PER_CLUSTER_HEAD,
+            routing: None,
+            scorer: Box::new(ScoringPreset::EnergyCentrality.weights()),
+            handover: None,
+            clusterer: Box::new(KMeans::new(0)),
+            is_clustered: false,
+            recluster_interval_rounds: ZCR_RECLUSTER_INTERVAL_ROUNDS,
+            recluster_triggers: ReclusterTriggers::default(),
+            rounds_since_reclustering: 0,
+            cluster_energy_at_clustering: Vec::new(),
+            previous_cluster_head_ids: Vec::new(),
+        }
+    }
+
+    /// Chooses how many clusters to form (analytic k_opt, elbow, silhouette, ...)
+    /// instead of `ceil(p · alive nodes)`.
+    pub fn with_cluster_count(mut self, cluster_count: ClusterCount) -> Self {
+        self.cluster_count = cluster_count;
+        self
+    }
+
+    /// Enables mid-round handover: a CH dropping below the threshold passes its
+    /// role to the best-scoring member of its cluster.
+    pub fn with_handover(mut self, handover: HandoverConfig) -> Self {
+        self.handover = Some(handover);
+        self
+    }
+
+    /// Clusters with K-Means seeded by the given initialization (`WarmStart`
+    /// reuses the previous clustering's centroids).
+    pub fn with_centroid_init(self, init: CentroidInit) -> Self {
+        self.with_clusterer(KMeans::new(0).with_init(init))
+    }
+
+    /// Replaces K-Means with another clustering algorithm (K-Medoids, DBSCAN, ...).
+    pub fn with_clusterer(mut self, clusterer: impl Clusterer + 'static) -> Self {
+        self.clusterer = Box::new(clusterer);
+        self.is_clustered = false;
+        self
+    }
+
+    /// Keeps clusters fixed for `interval_rounds` rounds between K-Means runs.
+    pub fn with_recluster_interval(mut self, interval_rounds: usize) -> Self {
+        self.recluster_interval_rounds = interval_rounds.max(1);
+        self
+    }
+
+    /// Sets the conditions that force an early re-clustering.
+    pub fn with_recluster_triggers(mut self, triggers: ReclusterTriggers) -> Self {
+        self.recluster_triggers = triggers;
+        self
+    }
+
+    /// Sums the residual energy of alive nodes per cluster.
+    fn cluster_energies<S: NodeStore + ?Sized>(
+        nodes: &S,
+        cluster_assignments: &[Option<usize>],
+        n_clusters: usize,
+    ) -> Vec<f32> {
+        let mut energies = vec![0.0; n_clusters];
+
+        for (node_id, &cluster_idx) in cluster_assignments.iter().enumerate().take(nodes.node_count()) {
+            if let Some(cluster_idx) = cluster_idx
+                && nodes.is_alive(node_id)
+            {
+                energies[cluster_idx] += nodes.remaining_energy_j(node_id).max(0.0);
+            }
         }
+
+        energies
     }
 
-    /// Assigns selected cluster heads to near or far zone based on distance to base station.
-    /// Also marks them as cluster heads.
-    fn assign_zones(
+    /// Decides whether the clusters must be rebuilt this round:
+    /// no clustering yet, interval elapsed, a previous CH died, or a cluster's
+    /// energy dropped below the configured fraction.
+    fn needs_reclustering<S: NodeStore + ?Sized>(&self, nodes: &S) -> bool {
+        if !self.is_clustered {
+            return true;
+        }
+
+        // Counting the current round, the clusters have been in use for
+        // `rounds_since_reclustering + 1` rounds
+        if self.rounds_since_reclustering + 1 >= self.recluster_interval_rounds {
+            return true;
+        }
+
+        if self.recluster_triggers.on_cluster_head_death
+            && self
+                .previous_cluster_head_ids
+                .iter()
+                .flatten()
+                .any(|&ch_id| !nodes.is_alive(ch_id))
+        {
+            return true;
+        }
+
+        if let Some(fraction) = self.recluster_triggers.cluster_energy_drop_fraction {
+            let current = Zcr::cluster_energies(
+                nodes,
+                self.clusterer.clusters(),
+                self.clusterer.centers().len(),
+            );
+            let dropped = current
+                .iter()
+                .zip(&self.cluster_energy_at_clustering)
+                .any(|(&now, &initial)| now < initial * fraction);
+
+            if dropped {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Re-clusters the alive nodes into as many clusters as `cluster_count` asks for,
+    /// records each cluster's starting energy and makes every node eligible for the
+    /// CH role again.
+    fn recluster(&mut self, nodes: &mut dyn NodeStore, area_m2: f32, rng: &mut dyn RngCore) {
+        self.num_cluster_heads = self.cluster_count.resolve(nodes, area_m2, rng);
+        self.clusterer.set_n_clusters(self.num_cluster_heads);
+        self.clusterer.fit_alive(nodes, rng);
+
+        self.cluster_energy_at_clustering = Zcr::cluster_energies(
+            nodes,
+            self.clusterer.clusters(),
+            self.clusterer.centers().len(),
+        );
+        self.previous_cluster_head_ids.clear();
+        self.rounds_since_reclustering = 0;
+        self.is_clustered = true;
+
+        for node_id in 0..nodes.node_count() {
+            nodes.set_eligible_for_ch(node_id, true);
+        }
+    }
+
+    /// Starts a new rotation cycle in every cluster whose alive members have all
+    /// served as CH since the last re-clustering.
+    fn refresh_rotation<S: NodeStore + ?Sized>(
+        nodes: &mut S,
+        cluster_assignments: &[Option<usize>],
+        n_clusters: usize,
+    ) {
+        let mut has_eligible = vec![false; n_clusters];
+        let cluster_assignments = &cluster_assignments[..cluster_assignments.len().min(nodes.node_count())];
+
+        for (node_id, &cluster_idx) in cluster_assignments.iter().enumerate() {
+            if let Some(cluster_idx) = cluster_idx
+                && nodes.is_alive(node_id)
+                && nodes.is_eligible_for_ch(node_id)
+            {
+                has_eligible[cluster_idx] = true;
+            }
+        }
+
+        for (node_id, &cluster_idx) in cluster_assignments.iter().enumerate() {
+            if let Some(cluster_idx) = cluster_idx
+                && !has_eligible[cluster_idx]
+            {
+                nodes.set_eligible_for_ch(node_id, true);
+            }
+        }
+    }
+
+    /// Replaces the CH scoring function — either `ScoreWeights` (e.g. from a
+    /// `ScoringPreset`) or any closure over `CandidateFeatures`.
+    pub fn with_scorer(mut self, scorer: impl ClusterHeadScorer + 'static) -> Self {
+        self.scorer = Box::new(scorer);
+        self
+    }
+
+    /// Sets how many far-zone CHs a single near-zone CH will relay for per round.
+    pub fn with_max_relays_per_cluster_head(mut self, max_relays: usize) -> Self {
+        self.max_relays_per_cluster_head = max_relays;
+        self
+    }
+
+    /// Forwards aggregated data over the given inter-cluster router
+    /// instead of the near/far zone relay.
+    pub fn with_routing(mut self, router: InterClusterRouter) -> Self {
+        self.routing = Some(router);
+        self
+    }
+
+    /// Applies CH → sink energy dissipation when CH-level routing is enabled:
+    /// one hop along each CH's least-cost route.
+    ///
+    /// `reading_origin_ids` holds the readings aggregated by each CH, indexed by node id.
+    fn dissipate_routed_cluster_head_energy(
+        &self,
+        router: &InterClusterRouter,
+        simulator: &mut Simulator,
+        reading_origin_ids: &mut [Vec<usize>],
+    ) {
+        let Simulator { nodes, columns, distance_cache, link, rng, metrics, field, .. } = simulator;
+        let nodes = active_store(nodes, columns);
+        let cluster_head_ids: Vec<usize> = self.zone_cluster_heads.concat();
+        let aggregates: Vec<Vec<usize>> = cluster_head_ids
+            .iter()
+            .map(|&ch_id| std::mem::take(&mut reading_origin_ids[ch_id]))
+            .collect();
+
+        router
+            .build_routes(nodes, &cluster_head_ids, distance_cache.as_ref(), link)
+            .forward_to_sink(nodes, &aggregates, link, rng, metrics, field.as_mut());
+    }
+
+    /// Marks the selected nodes as cluster heads, records the round they served in
+    /// and removes them from the rotation until their cluster's cycle restarts.
+    fn mark_cluster_heads<S: NodeStore + ?Sized>(
+        selected_cluster_head_ids: &[Option<usize>],
+        nodes: &mut S,
+        current_round: usize,
+    ) {
+        for &ch_id in selected_cluster_head_ids.iter().flatten() {
+            nodes.promote_to_cluster_head(ch_id, current_round);
+        }
+    }
+
+    /// Assigns cluster heads to near or far zone based on distance to base station.
+    fn assign_zones<S: NodeStore + ?Sized>(
         &mut self,
         selected_cluster_head_ids: &[Option<usize>],
-        nodes: &mut Vec<Node>,
+        nodes: &S,
     ) {
         // Clear previous assignments
         self.zone_cluster_heads[0].clear();
@@ -54,112 +364,268 @@ impl Zcr {
 
         for opt_ch_id in selected_cluster_head_ids.iter().flatten() {
             let ch_id = *opt_ch_id;
-            let distance_to_bs = nodes[ch_id].distance_to_base_station_m;
+            let distance_to_bs = nodes.distance_to_base_station_m(ch_id);
 
             if distance_to_bs <= FS_MULTIPATH_THRESHOLD_DISTANCE_M {
                 self.zone_cluster_heads[1].push(ch_id); // near zone
             } else {
                 self.zone_cluster_heads[0].push(ch_id); // far zone
             }
-
-            nodes[ch_id].is_cluster_head = true;
         }
     }
 
     /// Performs cluster formation:
     /// - Assigns alive non-CH nodes to their cluster's selected CH
+    /// - Nodes without a cluster (e.g. DBSCAN noise), whose cluster has no CH or
+    ///   whose CH is out of radio range join the nearest selected CH if it is in range
     /// - Deducts transmission energy from member nodes to their CH
-    fn form_clusters(
-        &mut self,
+    ///
+    /// Returns the nodes with no CH in range; the caller sends their reading straight
+    /// to the sink (they are reported isolated when the sink is out of range too).
+    fn form_clusters<S: NodeStore + ?Sized>(
         selected_cluster_head_ids: &[Option<usize>],
-        nodes: &mut Vec<Node>,
-        cluster_assignments: &[usize],
-    ) {
+        nodes: &mut S,
+        cluster_assignments: &[Option<usize>],
+        distances: Option<&DistanceCache>,
+        link: &LinkLayer,
+        metrics: &mut RoundMetrics,
+    ) -> Vec<usize> {
+        let cluster_head_ids: Vec<usize> = selected_cluster_head_ids.iter().flatten().copied().collect();
+        let cluster_head_grid = SpatialGrid::from_nodes(nodes, &cluster_head_ids);
+        let mut direct_ids: Vec<usize> = Vec::new();
+
         for (node_id, &cluster_idx) in cluster_assignments.iter().enumerate() {
 
-            if !nodes[node_id].is_alive || nodes[node_id].is_cluster_head {
+            if !nodes.is_alive(node_id) || nodes.is_cluster_head(node_id) {
                 continue;
             }
 
-            if let Some(ch_id) = selected_cluster_head_ids[cluster_idx] {
-                nodes[node_id].cluster_head_id = Some(ch_id);
-                nodes[ch_id].cluster_member_ids.push(node_id);
-
-                let distance_to_ch = (nodes[node_id].position - nodes[ch_id].position).length();
-                nodes[node_id].remaining_energy_j -=
-                    calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance_to_ch);
+            let in_range_ch = |ch_id: usize| {
+                let distance_to_ch = node_distance(nodes, distances, node_id, ch_id);
+                link.in_range(nodes, node_id, distance_to_ch).then_some((ch_id, distance_to_ch))
+            };
+
+            let cluster_ch_id = cluster_idx.and_then(|idx| selected_cluster_head_ids[idx]);
+            let ch = cluster_ch_id.and_then(in_range_ch).or_else(|| {
+                cluster_head_grid
+                    .nearest(nodes.position(node_id))
+                    .and_then(|(ch_id, _)| in_range_ch(ch_id))
+            });
+
+            if let Some((ch_id, distance_to_ch)) = ch {
+                nodes.join_cluster(node_id, ch_id);
+                nodes.consume_energy(
+                    node_id,
+                    calculate_transmit_energy(DATA_PACKET_SIZE_BITS, distance_to_ch),
+                );
+                metrics.record_packets(node_id, |packets| packets.sent_to_cluster_head += 1);
+            } else {
+                direct_ids.push(node_id);
             }
         }
+
+        nodes.finish_cluster_formation();
+        direct_ids
     }
 
-    /// Applies energy dissipation for all cluster heads:
-    /// - Far-zone CHs: either direct to BS or relay via nearest near-zone CH
-    /// - Near-zone CHs: always direct to BS
-    /// - All CHs deduct RX + aggregation for their members
-    fn dissipate_cluster_head_energy(&self, nodes: &mut Vec<Node>) {
-        // Far-zone CHs (may relay)
-        for &far_ch_id in &self.zone_cluster_heads[0] {
+    /// Chooses a relay for every far-zone cluster head.
+    ///
+    /// A relay candidate is scored by the total path energy (far → near hop,
+    /// RX + aggregation at the near CH, near → BS hop) divided by the near CH's
+    /// normalized residual energy, so depleted CHs look more expensive.
+    /// A near CH accepts at most `max_relays_per_cluster_head` far CHs per round,
+    /// and a relay is only used when its path energy beats the direct transmission.
+    /// Both hops must lie within radio range; a far CH that cannot reach the BS
+    /// takes any in-range relay.
+    ///
+    /// Returns one entry per far-zone CH (same order as `zone_cluster_heads[0]`):
+    /// `Some(near_ch_id)` to relay, `None` to transmit directly to the base station.
+    fn select_relays<S: NodeStore + ?Sized>(
+        &self,
+        nodes: &S,
+        distances: Option<&DistanceCache>,
+        link: &LinkLayer,
+    ) -> Vec<Option<usize>> {
+        let far_ch_ids = &self.zone_cluster_heads[0];
+        let near_ch_ids = &self.zone_cluster_heads[1];
+
+        let mut relays: Vec<Option<usize>> = vec![None; far_ch_ids.len()];
+        let mut relay_load: Vec<usize> = vec![0; near_ch_ids.len()];
+
+        // Near CHs indexed by their position in `near_ch_ids`
+        let near_ch_grid = SpatialGrid::new(
+            near_ch_ids
+                .iter()
+                .enumerate()
+                .map(|(near_idx, &near_ch_id)| (near_idx, nodes.position(near_ch_id))),
+        );
+
+        // Farthest CHs pick first: they gain the most from relaying
+        let mut order: Vec<usize> = (0..far_ch_ids.len()).collect();
+        order.sort_by(|&a, &b| {
+            nodes
+                .distance_to_base_station_m(far_ch_ids[b])
+                .total_cmp(&nodes.distance_to_base_station_m(far_ch_ids[a]))
+        });
+
+        for far_idx in order {
+            let far_ch_id = far_ch_ids[far_idx];
+            let far_distance_to_bs = nodes.distance_to_base_station_m(far_ch_id);
+            let far_range_m = link.radio_range().max_range_m(nodes.radio_class(far_ch_id));
+            let direct_energy = if far_distance_to_bs <= far_range_m {
+                calculate_transmit_energy(DATA_PACKET_SIZE_BITS, far_distance_to_bs)
+            } else {
+                f32::INFINITY
+            };
+
+            let mut best_weighted_cost = f32::INFINITY;
+            let mut best_near_idx: Option<usize> = None;
+
+            // A relay farther away than the BS can never beat the direct transmission
+            let mut candidate_idxs: Vec<usize> = near_ch_grid
+                .within_radius(nodes.position(far_ch_id), far_distance_to_bs.min(far_range_m))
+                .into_iter()
+                .map(|(near_idx, _)| near_idx)
+                .collect();
+            candidate_idxs.sort_unstable();
+
+            for near_idx in candidate_idxs {
+                let near_ch_id = near_ch_ids[near_idx];
+                let near_energy_j = nodes.remaining_energy_j(near_ch_id);
+                let near_distance_to_bs = nodes.distance_to_base_station_m(near_ch_id);
+
+                if relay_load[near_idx] >= self.max_relays_per_cluster_head
+                    || near_energy_j <= 0.0
+                    || !link.in_range(nodes, near_ch_id, near_distance_to_bs)
+                {
+                    continue;
+                }
+
+                let hop_distance = node_distance(nodes, distances, far_ch_id, near_ch_id);
+                if hop_distance > far_range_m {
+                    continue;
+                }
+                let path_energy = calculate_transmit_energy(DATA_PACKET_SIZE_BITS, hop_distance)
+                    + calculate_receive_energy(DATA_PACKET_SIZE_BITS)
+                    + calculate_aggregation_energy(DATA_PACKET_SIZE_BITS)
+                    + calculate_transmit_energy(DATA_PACKET_SIZE_BITS, near_distance_to_bs);
+
+                if path_energy >= direct_energy {
+                    continue;
+                }
 
-            let mut min_relay_distance = f32::INFINITY;
-            let mut best_near_ch_id: Option<usize> = None;
+                let residual_ratio = near_energy_j / INITIAL_NODE_ENERGY_J;
+                let weighted_cost = path_energy / residual_ratio;
 
-            // Find nearest near-zone CH
-            for &near_ch_id in &self.zone_cluster_heads[1] {
-                let distance = (nodes[far_ch_id].position - nodes[near_ch_id].position).length();
-                if distance < min_relay_distance {
-                    min_relay_distance = distance;
-                    best_near_ch_id = Some(near_ch_id);
+                if weighted_cost < best_weighted_cost {
+                    best_weighted_cost = weighted_cost;
+                    best_near_idx = Some(near_idx);
                 }
             }
 
-            let member_count = nodes[far_ch_id].cluster_member_ids.len() as f32;
+            if let Some(near_idx) = best_near_idx {
+                relay_load[near_idx] += 1;
+                relays[far_idx] = Some(near_ch_ids[near_idx]);
+            }
+        }
 
-            // RX + aggregation from members (always)
-            nodes[far_ch_id].remaining_energy_j -=
-                (calculate_receive_energy(DATA_PACKET_SIZE_BITS)
-                    + calculate_aggregation_energy(DATA_PACKET_SIZE_BITS))
-                    * member_count;
+        relays
+    }
 
+    /// Applies energy dissipation for all cluster heads:
+    /// - Far-zone CHs: either direct to BS or relay via the CH chosen by `select_relays`
+    /// - Near-zone CHs: always direct to BS, plus RX + aggregation for relayed packets
+    ///
+    /// Every hop crosses `link`; relayed readings arrive only if both hops succeed.
+    /// If a CH can reach the BS neither directly nor through a relay, the round falls
+    /// back to multi-hop routing over all CHs.
+    /// Member RX + aggregation is charged beforehand by `collect_member_data`.
+    /// `reading_origin_ids` holds the readings aggregated by each CH, indexed by node id.
+    fn dissipate_cluster_head_energy(&self, simulator: &mut Simulator, reading_origin_ids: &mut [Vec<usize>]) {
+        let Simulator { nodes, columns, distance_cache, link, rng, metrics, field, .. } = simulator;
+        let nodes = active_store(nodes, columns);
+        let distances = distance_cache.as_ref();
+        let relays = self.select_relays(nodes, distances, link);
+
+        let sink_in_range = |ch_id: usize| link.in_range(nodes, ch_id, nodes.distance_to_base_station_m(ch_id));
+        let stranded = self.zone_cluster_heads[0]
+            .iter()
+            .zip(&relays)
+            .any(|(&far_ch_id, relay)| relay.is_none() && !sink_in_range(far_ch_id))
+            || self.zone_cluster_heads[1].iter().any(|&near_ch_id| !sink_in_range(near_ch_id));
+
+        if stranded {
+            let cluster_head_ids: Vec<usize> = self.zone_cluster_heads.concat();
+            let aggregates: Vec<Vec<usize>> = cluster_head_ids
+                .iter()
+                .map(|&ch_id| std::mem::take(&mut reading_origin_ids[ch_id]))
+                .collect();
+
+            InterClusterRouter::build_fallback_routes(nodes, &cluster_head_ids, distances, link)
+                .forward_to_sink(nodes, &aggregates, link, rng, metrics, field.as_mut());
+            return;
+        }
+
+        // Far CHs whose aggregate reached their relay, forwarded with the relay's packet
+        let mut relayed_ch_ids: Vec<(usize, usize)> = Vec::new();
+
+        // Far-zone CHs (may relay)
+        for (&far_ch_id, relay) in self.zone_cluster_heads[0].iter().zip(relays) {
             // Transmission to BS or relay
-            if let Some(near_ch_id) = best_near_ch_id {
-                if min_relay_distance < nodes[far_ch_id].distance_to_base_station_m {
-                    // Relay via near CH
-                    nodes[far_ch_id].remaining_energy_j -=
-                        calculate_transmit_energy(DATA_PACKET_SIZE_BITS, min_relay_distance);
-
-                    // Near CH receives the relayed packet
-                    nodes[near_ch_id].remaining_energy_j -=
-                        calculate_receive_energy(DATA_PACKET_SIZE_BITS)
-                            + calculate_aggregation_energy(DATA_PACKET_SIZE_BITS);
-                } else {
-                    // Direct to BS is cheaper
-                    nodes[far_ch_id].remaining_energy_j -= calculate_transmit_energy(
-                        DATA_PACKET_SIZE_BITS,
-                        nodes[far_ch_id].distance_to_base_station_m,
+            if let Some(near_ch_id) = relay {
+                // Relay via near CH
+                let relay_distance = node_distance(nodes, distances, far_ch_id, near_ch_id);
+                let outcome = link.transmit(relay_distance, rng);
+                nodes.consume_energy(
+                    far_ch_id,
+                    calculate_transmit_energy(DATA_PACKET_SIZE_BITS, relay_distance) * outcome.attempts as f32,
+                );
+                metrics.record_packets(far_ch_id, |packets| packets.sent_to_cluster_head += 1);
+                metrics.record_link_outcome(far_ch_id, outcome);
+                if let Some(field) = field.as_ref() {
+                    let extra_bits = field.extra_payload_bits(&reading_origin_ids[far_ch_id]);
+                    charge_extra_payload(
+                        nodes,
+                        far_ch_id,
+                        Some(near_ch_id),
+                        relay_distance,
+                        outcome.attempts,
+                        extra_bits,
+                        metrics,
                     );
                 }
+
+                // Near CH receives the relayed packet
+                let mut receive_energy = calculate_receive_energy(DATA_PACKET_SIZE_BITS) * outcome.attempts as f32;
+                if outcome.delivered {
+                    receive_energy += calculate_aggregation_energy(DATA_PACKET_SIZE_BITS);
+                    metrics.record_packets(near_ch_id, |packets| {
+                        packets.aggregated += 1;
+                        packets.relayed += 1;
+                    });
+                    relayed_ch_ids.push((far_ch_id, near_ch_id));
+                } else {
+                    metrics.record_dropped_readings(&reading_origin_ids[far_ch_id]);
+                }
+                nodes.consume_energy(near_ch_id, receive_energy);
             } else {
-                // No near CH available → direct
-                nodes[far_ch_id].remaining_energy_j -= calculate_transmit_energy(
-                    DATA_PACKET_SIZE_BITS,
-                    nodes[far_ch_id].distance_to_base_station_m,
-                );
+                // No worthwhile relay → direct
+                send_to_sink(nodes, far_ch_id, &[&reading_origin_ids[far_ch_id]], link, rng, metrics, field.as_mut());
             }
         }
 
-        // Near-zone CHs: always direct to BS + RX/agg from members
+        // Near-zone CHs: always direct to BS, carrying the aggregates they relay
         for &near_ch_id in &self.zone_cluster_heads[1] {
-            let member_count = nodes[near_ch_id].cluster_member_ids.len() as f32;
-
-            nodes[near_ch_id].remaining_energy_j -=
-                member_count
-                    * (calculate_receive_energy(DATA_PACKET_SIZE_BITS)
-                        + calculate_aggregation_energy(DATA_PACKET_SIZE_BITS));
-
-            nodes[near_ch_id].remaining_energy_j -= calculate_transmit_energy(
-                DATA_PACKET_SIZE_BITS,
-                nodes[near_ch_id].distance_to_base_station_m,
-            );
+            let aggregates: Vec<&[usize]> = std::iter::once(near_ch_id)
+                .chain(
+                    relayed_ch_ids
+                        .iter()
+                        .filter(|&&(_, relay_id)| relay_id == near_ch_id)
+                        .map(|&(far_ch_id, _)| far_ch_id),
+                )
+                .map(|ch_id| reading_origin_ids[ch_id].as_slice())
+                .collect();
+            send_to_sink(nodes, near_ch_id, &aggregates, link, rng, metrics, field.as_mut());
         }
     }
 }
@@ -169,50 +635,110 @@ impl Protocol for Zcr {
         "ZCR"
     }
 
+    /// Setup is centralized: nodes only listen for the BS assignment and the CH's
+    /// schedule; members sleep outside their TDMA slot.
+    fn duty_cycle(&self, _simulator: &Simulator) -> DutyCycle {
+        DutyCycle {
+            setup_listen_slots: 2,
+            sleep_outside_slot: true,
+        }
+    }
+
     fn run_round(&mut self, simulator: &mut Simulator) {
         if simulator.alive_node_count == 0 {
             return;
         }
 
-        // Determine number of desired cluster heads this round
-        self.num_cluster_heads =
-            (self.cluster_head_probability * simulator.alive_node_count as f32).ceil() as usize;
+        let area_m2 = simulator.area_width_m * simulator.area_height_m;
+        let area_diagonal_m = simulator.area_diagonal_m();
+        let predicted_harvest_j = simulator.predicted_harvest_j();
+        let Simulator {
+            nodes,
+            columns,
+            current_round,
+            alive_node_count,
+            rng,
+            metrics,
+            distance_cache,
+            link,
+            mac,
+            control,
+            field,
+            ..
+        } = simulator;
+        let nodes = active_store(nodes, columns);
+
+        // Reset round state and retire depleted nodes
+        nodes.reset_round_state();
+
+        for node_id in 0..nodes.node_count() {
+            if nodes.is_alive(node_id) && nodes.remaining_energy_j(node_id) <= 0.0 {
+                nodes.set_alive(node_id, false);
+                *alive_node_count -= 1;
+            }
+        }
 
-        if self.num_cluster_heads == 0 {
+        if *alive_node_count == 0 {
             return;
         }
+        metrics.record_generated_readings(nodes);
+
+        // Nodes report position and energy to the BS, which runs the centralized setup
+        if let Some(control) = control {
+            control.charge_status_reports(nodes, metrics);
+        }
 
-        // Spatial clustering with K-Means
-        let mut kmeans = KMeans::new(self.num_cluster_heads);
-        kmeans.fit(&simulator.nodes);
+        // Spatial clustering (only when the current clusters expire)
+        if self.needs_reclustering(nodes) {
+            self.recluster(nodes, area_m2, rng);
+        } else {
+            self.rounds_since_reclustering += 1;
+        }
 
-        // Select best CH candidate per cluster using energy + centrality score
+        let clusterer = &self.clusterer;
+        self.num_cluster_heads = clusterer.centers().len();
+
+        Zcr::refresh_rotation(nodes, clusterer.clusters(), self.num_cluster_heads);
+
+        let degrees = distance_cache
+            .as_ref()
+            .and_then(|cache| cache.alive_node_degrees(nodes, CH_SCORING_NEIGHBOUR_RADIUS_M))
+            .unwrap_or_else(|| alive_node_degrees(nodes, CH_SCORING_NEIGHBOUR_RADIUS_M));
+        let max_harvest_scale = (0..nodes.node_count())
+            .filter(|&node_id| nodes.is_alive(node_id) && nodes.is_eligible_for_ch(node_id))
+            .map(|node_id| nodes.harvest_scale(node_id))
+            .fold(0.0, f32::max);
+        let context = ScoringContext {
+            area_diagonal_m,
+            current_round: *current_round,
+            recency_horizon_rounds: (1.0 / self.cluster_head_probability).round() as usize,
+            alive_node_count: *alive_node_count,
+            predicted_harvest_j,
+            max_predicted_harvest_j: predicted_harvest_j * max_harvest_scale,
+        };
+
+        // Select best eligible CH candidate per cluster using the configured scoring function
         let mut selected_cluster_head_ids: Vec<Option<usize>> =
             vec![None; self.num_cluster_heads];
         let mut best_scores: Vec<f32> = vec![f32::NEG_INFINITY; self.num_cluster_heads];
 
-        for (node_id, &cluster_idx) in kmeans.clusters().iter().enumerate() {
-            let node = &mut simulator.nodes[node_id];
-
-            reset_node_for_new_round(node);
-
-            // Check for energy depletion
-            if node.is_alive && node.remaining_energy_j <= 0.0 {
-                node.is_alive = false;
-                simulator.alive_node_count -= 1;
+        for (node_id, &cluster_idx) in clusterer.clusters().iter().enumerate().take(nodes.node_count()) {
+            let Some(cluster_idx) = cluster_idx else {
                 continue;
-            }
+            };
 
-            if !node.is_alive {
+            if !nodes.is_alive(node_id) || !nodes.is_eligible_for_ch(node_id) {
                 continue;
             }
 
-            // Score = normalized remaining energy - normalized distance to centroid
-            let energy_score = node.remaining_energy_j / INITIAL_NODE_ENERGY_J;
-            let distance_to_centroid = (node.position - kmeans.centroids()[cluster_idx]).length();
-            let distance_penalty = distance_to_centroid / 707.0; // ≈ sqrt(500² + 500²)
-
-            let score = energy_score - distance_penalty;
+            let features = CandidateFeatures::extract(
+                nodes,
+                node_id,
+                clusterer.centers()[cluster_idx],
+                degrees[node_id],
+                &context,
+            );
+            let score = self.scorer.score(&features);
 
             if score > best_scores[cluster_idx] {
                 best_scores[cluster_idx] = score;
@@ -220,17 +746,188 @@ impl Protocol for Zcr {
             }
         }
 
-        // Zone assignment + mark CHs
-        self.assign_zones(&selected_cluster_head_ids, &mut simulator.nodes);
+        // Mark CHs
+        Zcr::mark_cluster_heads(&selected_cluster_head_ids, nodes, *current_round);
+
+        // The BS pushes roles and cluster assignments back down
+        if let Some(control) = control {
+            control.charge_assignments(nodes, metrics);
+        }
 
         // Member assignment + member → CH energy cost
-        self.form_clusters(
+        let direct_ids = Zcr::form_clusters(
             &selected_cluster_head_ids,
-            &mut simulator.nodes,
-            kmeans.clusters(),
+            nodes,
+            clusterer.clusters(),
+            distance_cache.as_ref(),
+            link,
+            metrics,
         );
+        for &node_id in &direct_ids {
+            send_to_sink(nodes, node_id, &[&[node_id]], link, rng, metrics, field.as_mut());
+        }
+
+        // TDMA schedules of the data phase (charged by the MAC layer when enabled)
+        let cluster_head_ids: Vec<usize> = selected_cluster_head_ids.iter().flatten().copied().collect();
+        if let Some(control) = control
+            && mac.is_none()
+        {
+            control.charge_schedules(nodes, &cluster_head_ids, distance_cache.as_ref(), metrics);
+        }
+        let tdma = mac.map(|mac| schedule_tdma(nodes, &cluster_head_ids, distance_cache.as_ref(), &mac, metrics));
+
+        // Steady-state frames with the clusters formed above: CH RX + aggregation of
+        // member data (with optional mid-round handover), then CH → sink
+        let mut frame_cluster_head_ids = selected_cluster_head_ids.clone();
+
+        for frame in 0..simulator.frames_per_round {
+            if frame > 0 {
+                start_data_frame(simulator, &direct_ids);
+            }
+
+            let mut forwarding_cluster_head_ids: Vec<Option<usize>> =
+                vec![None; self.num_cluster_heads];
+            let mut reading_origin_ids: Vec<Vec<usize>> = vec![Vec::new(); simulator.node_store().node_count()];
+
+            for (cluster_idx, &opt_ch_id) in frame_cluster_head_ids.iter().enumerate() {
+                let Some(ch_id) = opt_ch_id else {
+                    continue;
+                };
+                if !simulator.node_store().is_alive(ch_id) {
+                    continue;
+                }
+
+                let centroid = self.clusterer.centers()[cluster_idx];
+                let handover_score = |nodes: &dyn NodeStore, id: usize| {
+                    self.scorer
+                        .score(&CandidateFeatures::extract(nodes, id, centroid, degrees[id], &context))
+                };
+
+                let aggregate = collect_member_data(
+                    simulator,
+                    ch_id,
+                    self.handover.as_ref(),
+                    &handover_score,
+                    tdma.as_ref(),
+                );
+
+                if let Some((final_ch_id, origin_ids)) = aggregate {
+                    forwarding_cluster_head_ids[cluster_idx] = Some(final_ch_id);
+                    reading_origin_ids[final_ch_id] = origin_ids;
+                }
+            }
+
+            // Cluster quality of the structure after the first frame; partitions
+            // without a CH count as empty
+            if frame == 0 && simulator.collect_cluster_quality {
+                let mut cluster_quality = ClusterQuality::from_cluster_heads(simulator.node_store());
+                cluster_quality.empty_clusters +=
+                    selected_cluster_head_ids.iter().filter(|id| id.is_none()).count();
+                simulator.metrics.cluster_quality = cluster_quality;
+            }
+
+            // Zone assignment of the CHs that still hold data
+            self.assign_zones(&forwarding_cluster_head_ids, simulator.node_store());
+
+            // CH → sink energy costs (TX direct, relayed or routed)
+            match &self.routing {
+                Some(router) => {
+                    self.dissipate_routed_cluster_head_energy(router, simulator, &mut reading_origin_ids)
+                }
+                None => self.dissipate_cluster_head_energy(simulator, &mut reading_origin_ids),
+            }
+
+            // A CH that died keeps its entry, so the next frames skip its cluster
+            for (frame_ch_id, forwarding) in frame_cluster_head_ids.iter_mut().zip(&forwarding_cluster_head_ids) {
+                *frame_ch_id = forwarding.or(*frame_ch_id);
+            }
+        }
+
+        // Remember this round's CHs (a CH that died mid-round stays recorded
+        // so the CH-death trigger can fire)
+        self.previous_cluster_head_ids = frame_cluster_head_ids;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BASE_STATION_POSITION;
+    use crate::node::Node;
+    use glam::Vec2;
+
+    /// Runs `rounds` ZCR rounds without early triggers and counts the re-clusterings.
+    fn count_reclusterings(interval_rounds: usize, rounds: usize) -> usize {
+        let mut simulator = Simulator::with_seed(300.0, 300.0, 60, 7);
+        let mut zcr = Zcr::new(0.1)
+            .with_recluster_interval(interval_rounds)
+            .with_recluster_triggers(ReclusterTriggers {
+                on_cluster_head_death: false,
+                cluster_energy_drop_fraction: None,
+            });
+
+        (0..rounds)
+            .filter(|_| {
+                simulator.update(&mut zcr);
+                zcr.rounds_since_reclustering == 0
+            })
+            .count()
+    }
+
+    #[test]
+    fn reclusters_every_round_with_interval_one() {
+        assert_eq!(count_reclusterings(1, 9), 9);
+    }
+
+    #[test]
+    fn reclusters_every_interval_rounds() {
+        // Rounds 1, 4, 7 and 10
+        assert_eq!(count_reclusterings(3, 10), 4);
+    }
+
+    /// Nodes at the given offsets from the base station (meters).
+    fn nodes_around_sink(offsets: &[(f32, f32)]) -> Vec<Node> {
+        offsets
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| Node::new(id, BASE_STATION_POSITION + Vec2::new(x, y)))
+            .collect()
+    }
+
+    /// Relays chosen for the far-zone CHs `far` through the near-zone CHs `near`.
+    fn select_relays(nodes: &Vec<Node>, far: &[usize], near: &[usize], max_relays: usize) -> Vec<Option<usize>> {
+        let mut zcr = Zcr::new(0.1).with_max_relays_per_cluster_head(max_relays);
+        zcr.zone_cluster_heads = vec![far.to_vec(), near.to_vec()];
+        zcr.select_relays(nodes, None, &LinkLayer::default())
+    }
+
+    #[test]
+    fn far_cluster_head_relays_when_cheaper_than_direct() {
+        // 200 m direct (multipath) vs. two 100 m hops
+        let nodes = nodes_around_sink(&[(200.0, 0.0), (100.0, 0.0)]);
+        assert_eq!(select_relays(&nodes, &[0], &[1], 2), [Some(1)]);
+
+        // 60 m direct vs. two hops each paying the electronics again
+        let nodes = nodes_around_sink(&[(60.0, 0.0), (30.0, 0.0)]);
+        assert_eq!(select_relays(&nodes, &[0], &[1], 2), [None]);
+    }
+
+    #[test]
+    fn relay_selection_prefers_energy_rich_cluster_heads() {
+        // Two relays at the same distance; node 1 has little energy left
+        let mut nodes = nodes_around_sink(&[(200.0, 0.0), (100.0, 10.0), (100.0, -10.0)]);
+        nodes[1].remaining_energy_j = 0.2;
+        assert_eq!(select_relays(&nodes, &[0], &[1, 2], 2), [Some(2)]);
+
+        nodes[1].remaining_energy_j = INITIAL_NODE_ENERGY_J;
+        nodes[2].remaining_energy_j = 0.2;
+        assert_eq!(select_relays(&nodes, &[0], &[1, 2], 2), [Some(1)]);
+    }
 
-        // All CH energy costs (RX/agg + TX direct or relayed)
-        self.dissipate_cluster_head_energy(&mut simulator.nodes);
+    #[test]
+    fn relay_load_is_capped_farthest_first() {
+        let nodes = nodes_around_sink(&[(200.0, 0.0), (210.0, 0.0), (220.0, 0.0), (100.0, 0.0)]);
+        assert_eq!(select_relays(&nodes, &[0, 1, 2], &[3], 2), [None, Some(3), Some(3)]);
+        assert_eq!(select_relays(&nodes, &[0, 1, 2], &[3], 3), [Some(3); 3]);
     }
 }